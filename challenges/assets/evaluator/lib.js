@@ -0,0 +1,89 @@
+const fs = require("fs");
+
+const _examples = [];
+
+function example(f) {
+  _examples.push(f);
+  return f;
+}
+
+// Return a function that generates deterministic pseudo random numbers in [0, 1) from a seed.
+function random(seed) {
+  let state = 0;
+  for (const c of String(seed)) {
+    state = Math.imul(state ^ c.charCodeAt(0), 2654435761);
+  }
+  return () => {
+    state = (state + 0x6d2b79f5) | 0;
+    let t = Math.imul(state ^ (state >>> 15), 1 | state);
+    t = (t + Math.imul(t ^ (t >>> 7), 61 | t)) ^ t;
+    return ((t ^ (t >>> 14)) >>> 0) / 4294967296;
+  };
+}
+
+// Return a random integer between a and b (both inclusive) using a function returned by `random`.
+function randint(rng, a, b) {
+  return a + Math.floor(rng() * (b - a + 1));
+}
+
+function _read() {
+  return JSON.parse(fs.readFileSync(0, "utf8"));
+}
+
+function _print(obj) {
+  console.log(JSON.stringify(obj));
+}
+
+function main(Input, Output, prepare = null) {
+  const [command, seed] = process.argv.slice(2);
+  if (command === "examples") {
+    _print(_examples.map((_, i) => `_ex_${i}`));
+  } else if (command === "generate") {
+    let inp;
+    if (seed.slice(0, 4) === "_ex_") {
+      inp = _examples[parseInt(seed.slice(4))]();
+    } else {
+      inp = Input.fromSeed(seed);
+    }
+    const out = { input: inp.serialize(), data: inp.data() ?? null };
+    if (typeof inp.weight === "function") {
+      out.weight = inp.weight();
+    }
+    if (typeof inp.output === "function") {
+      out.output = inp.output();
+    }
+    _print(out);
+  } else if (command === "prepare") {
+    const obj = _read();
+    const env = obj.environment;
+    const code = obj.code;
+    const files = Object.fromEntries((obj.files ?? []).map((file) => [file.name, file.content]));
+    const data = obj.data;
+    if (prepare === null) {
+      _print({ code, reason: "" });
+      return;
+    }
+    const logs = [];
+    try {
+      const out = prepare(env, code, data, (msg) => logs.push(msg), files);
+      _print({ code: out, reason: logs.join("\n") });
+    } catch {
+      _print({ code: null, reason: logs.join("\n") });
+    }
+  } else if (command === "check") {
+    const obj = _read();
+    const data = obj.data;
+    const logs = [];
+    let out;
+    try {
+      out = Output.deserialize(obj.output, (msg) => logs.push(msg));
+    } catch {
+      _print({ verdict: "INVALID_OUTPUT_FORMAT", reason: logs.join("\n") });
+      return;
+    }
+    const ok = out.check(data, (msg) => logs.push(msg));
+    _print({ verdict: ok ? "OK" : "WRONG_ANSWER", reason: logs.join("\n") });
+  }
+}
+
+module.exports = { example, main, random, randint };
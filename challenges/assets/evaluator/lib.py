@@ -1,3 +1,4 @@
+import inspect
 import json
 import random
 import sys
@@ -21,19 +22,27 @@ def main(Input, Output, prepare=None):
             inp = _examples[int(seed[4:])]()
         else:
             inp = Input.from_seed(seed)
-        print(json.dumps({"input": inp.serialize(), "data": inp.data()}))
+        out = {"input": inp.serialize(), "data": inp.data()}
+        if hasattr(inp, "weight"):
+            out["weight"] = inp.weight()
+        if hasattr(inp, "output"):
+            out["output"] = inp.output()
+        print(json.dumps(out))
     elif sys.argv[1] == "prepare":
         with open(0) as f:
             obj = json.load(f)
         env = obj["environment"]
         code = obj["code"]
+        files = {file["name"]: file["content"] for file in obj.get("files", [])}
         data = obj["data"]
         if prepare is None:
             print(json.dumps({"code": code, "reason": ""}))
             sys.exit()
+        # older evaluators don't know about additional files
+        kwargs = {"files": files} if "files" in inspect.signature(prepare).parameters else {}
         logs = []
         try:
-            out = prepare(env, code, data, logs.append)
+            out = prepare(env, code, data, logs.append, **kwargs)
         except:
             print(json.dumps({"code": None, "reason": "\n".join(logs)}))
         else:
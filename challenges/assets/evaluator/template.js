@@ -0,0 +1,132 @@
+const { example, main, random, randint } = require("./lib.js");
+
+class Input {
+  constructor(n) {
+    this.n = n;
+  }
+
+  /**
+   * Generate a challenge input from a seed. This seed is usually a string or an integer, but could
+   * be any value. This function should be deterministic, i.e. if called several times with the same
+   * seed, it should always generate the same challenge input.
+   */
+  static fromSeed(seed) {
+    // initialize a random number generator with the given seed
+    const rng = random(seed);
+
+    // generate a random challenge input and return it
+    const n = randint(rng, 10, 10000);
+    return new Input(n);
+  }
+
+  /**
+   * Convert an Input object into the string that is fed into the user's solution program via stdin.
+   */
+  serialize() {
+    return String(this.n);
+  }
+
+  /**
+   * Return additional information that is fed into the `Output.check` function when checking the
+   * output of the user's solution program. This information is only available to this evaluator
+   * and not shared with the user. You can use this to calculate the expected challenge output or to
+   * return other information that are needed to check the solution's output (e.g. in case your
+   * evaluator should accept more than one specific output).
+   */
+  data() {
+    // in this case we just calculate and return the expected answer
+    return (this.n * (this.n + 1)) / 2;
+  }
+
+  /**
+   * Return the weight of this challenge input. This is only used if the coding challenge uses the
+   * `WEIGHTED` scoring mode, in which case the score of a submission is the sum of the weights of
+   * all passed tests divided by the sum of the weights of all tests.
+   */
+  weight() {
+    return 1;
+  }
+
+  /**
+   * Return the expected output of the user's solution program for this challenge input. This is
+   * only used if the coding challenge uses one of the built-in `EXACT`, `TOKENS` or `FLOAT`
+   * checkers instead of the `EVALUATOR` checker, in which case `Output.check` is not called at all.
+   * If this function is missing, the output of the sample solution is used instead.
+   */
+  output() {
+    return String(this.data());
+  }
+}
+
+class Output {
+  constructor(sum) {
+    this.sum = sum;
+  }
+
+  /**
+   * Parse the output of the user's solution program. Throwing an error here will result in the
+   * `INVALID_OUTPUT_FORMAT` verdict. You can use the `log` function to provide additional
+   * information on why parsing the output failed.
+   */
+  static deserialize(output, log) {
+    output = output.trim();
+    if (!/^\d+$/.test(output)) {
+      log("output is not a single integer");
+      throw new Error();
+    }
+
+    return new Output(parseInt(output));
+  }
+
+  /**
+   * Determine whether the output of the user's solution program (this) is correct and should be
+   * accepted. `data` is the value generated by the `Input.data` function for the corresponding
+   * challenge input. In case of an incorrect output you can use the `log` function to provide
+   * additional information on why the answer was wrong.
+   */
+  check(data, log) {
+    if (this.sum < data) {
+      log("too low");
+      return false; // `WRONG_ANSWER`
+    }
+    if (this.sum > data) {
+      log("too high");
+      return false; // `WRONG_ANSWER`
+    }
+
+    return true; // `OK`
+  }
+}
+
+// Provide example inputs that are shown to the user. The corresponding outputs and explanations are
+// generated automatically using the provided sample solution. `example` expects a single function
+// that returns a single Input object.
+example(() => new Input(5));
+example(() => new Input(10));
+example(() => Input.fromSeed(0));
+example(() => Input.fromSeed(1));
+example(() => Input.fromSeed(2));
+example(() => new Input(7 * 191));
+
+/**
+ * This function is called after a challenge input has been generated and before the user's
+ * solution program is run. `environment` is the name of the environment of the user's solution
+ * (e.g. "rust" or "python") and `code` is the actual code the user has uploaded. `files` maps the
+ * names of any additional source files the user has uploaded to their contents. Again, `data` is
+ * the value generated by the `Input.data` function, just like in `Output.check`. The string this
+ * function returns will replace the users code before running it. Throwing an error here will
+ * result in the `PRE_CHECK_FAILED` verdict. The purpose of this function is to enfoce additional
+ * contraints on solution programs and to prepare the environment in which the user's code will run.
+ */
+function prepare(environment, code, data, log, files) {
+  // if (environment === "python" && code.length > 64) {
+  //   log("your python program is too long");
+  //   throw new Error();
+  // }
+
+  return code;
+}
+
+// call the library's main function which implements the evaluator api using the provided Input and
+// Output classes
+main(Input, Output, prepare);
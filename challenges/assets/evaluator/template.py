@@ -39,6 +39,25 @@ class Input:
         # in this case we just calculate and return the expected answer
         return self.n * (self.n + 1) // 2
 
+    def weight(self) -> float:
+        """
+        Return the weight of this challenge input. This is only used if the coding challenge uses
+        the `WEIGHTED` scoring mode, in which case the score of a submission is the sum of the
+        weights of all passed tests divided by the sum of the weights of all tests.
+        """
+
+        return 1
+
+    def output(self) -> str:
+        """
+        Return the expected output of the user's solution program for this challenge input. This is
+        only used if the coding challenge uses one of the built-in `EXACT`, `TOKENS` or `FLOAT`
+        checkers instead of the `EVALUATOR` checker, in which case `Output.check` is not called at
+        all. If this function is missing, the output of the sample solution is used instead.
+        """
+
+        return str(self.data())
+
 
 @dataclass
 class Output:
@@ -100,11 +119,12 @@ def more_complex_example():
     return Input(n)
 
 
-def prepare(environment: str, code: str, data, log) -> str:
+def prepare(environment: str, code: str, data, log, files: dict[str, str]) -> str:
     """
     This function is called after a challenge input has been generated and before the user's solution
     program is run. `environment` is the name of the environment of the user's solution (e.g. "rust"
-    or "python") and `code` is the actual code the user has uploaded. Again, `data` is the value
+    or "python") and `code` is the actual code the user has uploaded. `files` maps the names of any
+    additional source files the user has uploaded to their contents. Again, `data` is the value
     generated by the `Input.data` function, just like in `Output.check`. The string this function
     returns will replace the users code before running it. Raising an error here will result in the
     `PRE_CHECK_FAILED` verdict. The purpose of this function is to enfoce additional contraints on
@@ -1,23 +1,87 @@
-use poem_openapi::{payload::PlainText, OpenApi};
+use poem_ext::static_string;
+use poem_openapi::{
+    param::Path,
+    payload::{Json, PlainText},
+    ApiResponse, Object, OpenApi,
+};
 
 use crate::{
     endpoints::Tags,
-    services::judge::{EVALUATOR_LIBRARY, EVALUATOR_TEMPLATE},
+    services::judge::{get_evaluator_assets, EvaluatorAssets, EVALUATOR_ASSETS},
 };
 
 pub struct Api;
 
 #[OpenApi(tag = "Tags::CodingChallenges")]
 impl Api {
+    /// Return the environments evaluators can be written for.
+    #[oai(path = "/coding_challenges/evaluator/environments", method = "get")]
+    async fn list_evaluator_environments(&self) -> Json<Vec<&'static str>> {
+        Json(
+            EVALUATOR_ASSETS
+                .iter()
+                .map(|assets| assets.environment)
+                .collect(),
+        )
+    }
+
     /// Return the evaluator template.
     #[oai(path = "/coding_challenges/evaluator/template.py", method = "get")]
     async fn get_evaluator_template(&self) -> PlainText<&'static str> {
-        PlainText(EVALUATOR_TEMPLATE)
+        PlainText(python_assets().template)
     }
 
     /// Return the evaluator library.
     #[oai(path = "/coding_challenges/evaluator/lib.py", method = "get")]
     async fn get_evaluator_lib(&self) -> PlainText<&'static str> {
-        PlainText(EVALUATOR_LIBRARY)
+        PlainText(python_assets().library)
+    }
+
+    /// Return the evaluator template for an environment.
+    #[oai(
+        path = "/coding_challenges/evaluator/:environment/template",
+        method = "get"
+    )]
+    async fn get_evaluator_template_for_environment(
+        &self,
+        environment: Path<String>,
+    ) -> GetEvaluatorAsset {
+        match get_evaluator_assets(&environment.0) {
+            Some(assets) => GetEvaluatorAsset::Ok(PlainText(assets.template)),
+            None => GetEvaluatorAsset::EnvironmentNotFound(Json(Default::default())),
+        }
+    }
+
+    /// Return the evaluator library for an environment.
+    #[oai(path = "/coding_challenges/evaluator/:environment/lib", method = "get")]
+    async fn get_evaluator_lib_for_environment(
+        &self,
+        environment: Path<String>,
+    ) -> GetEvaluatorAsset {
+        match get_evaluator_assets(&environment.0) {
+            Some(assets) => GetEvaluatorAsset::Ok(PlainText(assets.library)),
+            None => GetEvaluatorAsset::EnvironmentNotFound(Json(Default::default())),
+        }
     }
 }
+
+/// The legacy endpoints only serve the python evaluator assets.
+fn python_assets() -> &'static EvaluatorAssets {
+    get_evaluator_assets("python").expect("python evaluator assets are missing")
+}
+
+#[derive(ApiResponse)]
+enum GetEvaluatorAsset {
+    #[oai(status = 200)]
+    Ok(PlainText<&'static str>),
+    /// There is no evaluator library for this environment.
+    #[oai(status = 404)]
+    EnvironmentNotFound(Json<EnvironmentNotFoundError>),
+}
+
+static_string!(EnvironmentNotFoundText, "environment_not_found");
+
+#[derive(Debug, Default, Object)]
+struct EnvironmentNotFoundError {
+    error: EnvironmentNotFoundText,
+}
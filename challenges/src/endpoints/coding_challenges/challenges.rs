@@ -1,15 +1,18 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use chrono::{DateTime, Utc};
-use entity::{challenges_coding_challenges, sea_orm_active_enums::ChallengesSubtaskType};
-use fnct::format::JsonFormatter;
+use entity::{
+    challenges_coding_challenge_evaluators, challenges_coding_challenges,
+    sea_orm_active_enums::ChallengesSubtaskType,
+};
+use fnct::{format::JsonFormatter, key};
 use lib::{
     auth::{AdminAuth, VerifiedUserAuth},
     config::Config,
     Cache, SharedState,
 };
 use poem::web::Data;
-use poem_ext::{db::DbTxn, response};
+use poem_ext::{db::DbTxn, response, responses::ErrorResponse};
 use poem_openapi::{
     param::{Path, Query},
     payload::Json,
@@ -21,14 +24,15 @@ use schemas::challenges::coding_challenges::{
     SubmissionContent, UpdateCodingChallengeRequest,
 };
 use sea_orm::{ActiveModelTrait, Set, Unchanged};
+use serde_json::Value;
 use tracing::error;
 use uuid::Uuid;
 
-use super::{_CheckError, check_challenge, CheckChallenge};
+use super::{_CheckError, check_challenge, test_data::get_test_data, CheckChallenge, CheckError};
 use crate::{
     endpoints::Tags,
     services::{
-        judge::{self, get_executor_config, Judge},
+        judge::{self, get_evaluator_assets, get_executor_config, Checker, Judge},
         subtasks::{
             create_subtask, query_subtask, query_subtask_admin, query_subtasks, update_subtask,
             CreateSubtaskError, QuerySubtaskAdminError, QuerySubtasksFilter, UpdateSubtaskError,
@@ -95,12 +99,20 @@ impl Api {
         db: Data<&DbTxn>,
         auth: VerifiedUserAuth,
     ) -> GetCodingChallenge::Response<VerifiedUserAuth> {
+        let executor = get_executor_config(&self.judge_cache, &self.sandkasten).await?;
         match query_subtask::<challenges_coding_challenges::Entity, _>(
             &db,
             &auth.0,
             task_id.0,
             subtask_id.0,
-            CodingChallenge::from,
+            |cc, subtask| {
+                CodingChallenge::from(
+                    cc,
+                    subtask,
+                    &self.config.challenges.coding_challenges,
+                    &executor,
+                )
+            },
         )
         .await?
         {
@@ -134,7 +146,12 @@ impl Api {
             None => return GetExamples::subtask_not_found(),
         };
 
-        let judge = self.get_judge(&cc.evaluator);
+        let judge = Judge::new(
+            &self.sandkasten,
+            &self.judge_cache,
+            &self.config.challenges.coding_challenges,
+            &cc,
+        );
 
         let examples = match judge.examples().await {
             Err(judge::Error::EvaluatorFailed(err) | judge::Error::InvalidOutput(err)) => {
@@ -153,6 +170,7 @@ impl Api {
                     seed,
                     &cc.solution_environment,
                     &cc.solution_code,
+                    &[],
                     Some(cc.time_limit as _),
                     Some(cc.memory_limit as _),
                 )
@@ -224,6 +242,7 @@ impl Api {
             Ok(cc) => GetSolution::ok(SubmissionContent {
                 environment: cc.solution_environment,
                 code: cc.solution_code,
+                files: Vec::new(),
             }),
             Err(QuerySubtaskAdminError::NotFound) => GetSolution::subtask_not_found(),
             Err(QuerySubtaskAdminError::NoAccess) => GetSolution::forbidden(),
@@ -271,22 +290,60 @@ impl Api {
         if data.0.memory_limit > config.memory_limit {
             return CreateCodingChallenge::memory_limit_exceeded(config.memory_limit);
         }
+        if get_evaluator_assets(&data.0.evaluator_environment).is_none() {
+            return CreateCodingChallenge::evaluator_environment_not_found();
+        }
 
         let cc_id = Uuid::new_v4();
+        let evaluator_id = Uuid::new_v4();
+        let judge = Judge::with_evaluator(
+            &self.sandkasten,
+            &self.judge_cache,
+            &self.config.challenges.coding_challenges,
+            evaluator_id,
+            &data.0.evaluator_environment,
+            &data.0.evaluator,
+            Checker {
+                mode: data.0.checker,
+                reference_environment: &data.0.solution_environment,
+                reference_code: &data.0.solution_code,
+            },
+        );
         if let Err(result) = check_challenge(CheckChallenge {
-            judge: self.get_judge(&data.0.evaluator),
+            judge,
             challenge_id: cc_id,
             solution_environment: &data.0.solution_environment,
             solution_code: &data.0.solution_code,
+            solution_files: &[],
             time_limit: data.0.time_limit,
             memory_limit: data.0.memory_limit,
             static_tests: data.0.static_tests,
+            test_data: &[],
             random_tests: data.0.random_tests,
+            test_concurrency: self.config.challenges.coding_challenges.test_concurrency,
+            scoring_mode: data.0.scoring_mode,
+            on_progress: None,
+            verdict_cache: None,
         })
         .await?
         {
             return Ok(_CheckError::Response::from(result).into());
         }
+        match self
+            .check_starter_code(&judge, &data.0.starter_code)
+            .await?
+        {
+            Ok(()) => {}
+            Err(StarterCodeError::EnvironmentNotFound(environment)) => {
+                return CreateCodingChallenge::starter_code_environment_not_found(environment)
+            }
+            Err(StarterCodeError::Rejected(reason)) => {
+                return CreateCodingChallenge::starter_code_rejected(reason)
+            }
+            Err(StarterCodeError::Check(result)) => {
+                return Ok(_CheckError::Response::from(*result).into())
+            }
+        }
 
         let cc = challenges_coding_challenges::ActiveModel {
             subtask_id: Set(subtask.id),
@@ -294,14 +351,34 @@ impl Api {
             memory_limit: Set(data.0.memory_limit as _),
             static_tests: Set(data.0.static_tests as _),
             random_tests: Set(data.0.random_tests as _),
-            evaluator: Set(data.0.evaluator),
+            scoring_mode: Set(data.0.scoring_mode),
+            checker: Set(data.0.checker),
+            evaluator: Set(data.0.evaluator.clone()),
+            evaluator_id: Set(evaluator_id),
+            evaluator_environment: Set(data.0.evaluator_environment.clone()),
             description: Set(data.0.description),
             solution_environment: Set(data.0.solution_environment),
             solution_code: Set(data.0.solution_code),
+            starter_code: Set(Value::from_iter(data.0.starter_code)),
         }
         .insert(&***db)
         .await?;
-        CreateCodingChallenge::ok(CodingChallenge::from(cc, subtask))
+        challenges_coding_challenge_evaluators::ActiveModel {
+            id: Set(evaluator_id),
+            challenge_id: Set(cc.subtask_id),
+            creator: Set(auth.0.id),
+            creation_timestamp: Set(Utc::now().naive_utc()),
+            evaluator: Set(data.0.evaluator),
+            environment: Set(data.0.evaluator_environment),
+        }
+        .insert(&***db)
+        .await?;
+        CreateCodingChallenge::ok(CodingChallenge::from(
+            cc,
+            subtask,
+            &self.config.challenges.coding_challenges,
+            &config,
+        ))
     }
 
     /// Update a coding challenge.
@@ -342,24 +419,86 @@ impl Api {
         if *data.0.memory_limit.get_new(&(cc.memory_limit as _)) > config.memory_limit {
             return UpdateCodingChallenge::memory_limit_exceeded(config.memory_limit);
         }
+        let evaluator = data.0.evaluator.get_new(&cc.evaluator);
+        let evaluator_environment = data
+            .0
+            .evaluator_environment
+            .get_new(&cc.evaluator_environment);
+        if get_evaluator_assets(evaluator_environment).is_none() {
+            return UpdateCodingChallenge::evaluator_environment_not_found();
+        }
+
+        // every change of the evaluator creates a new revision
+        let evaluator_id = (*evaluator != cc.evaluator
+            || *evaluator_environment != cc.evaluator_environment)
+            .then(Uuid::new_v4);
 
+        let judge = Judge::with_evaluator(
+            &self.sandkasten,
+            &self.judge_cache,
+            &self.config.challenges.coding_challenges,
+            evaluator_id.unwrap_or(cc.evaluator_id),
+            evaluator_environment,
+            evaluator,
+            Checker {
+                mode: *data.0.checker.get_new(&cc.checker),
+                reference_environment: data
+                    .0
+                    .solution_environment
+                    .get_new(&cc.solution_environment),
+                reference_code: data.0.solution_code.get_new(&cc.solution_code),
+            },
+        );
         if let Err(result) = check_challenge(CheckChallenge {
-            judge: self.get_judge(data.0.evaluator.get_new(&cc.evaluator)),
+            judge,
             challenge_id: cc.subtask_id,
             solution_environment: data
                 .0
                 .solution_environment
                 .get_new(&cc.solution_environment),
             solution_code: data.0.solution_code.get_new(&cc.solution_code),
+            solution_files: &[],
             time_limit: *data.0.time_limit.get_new(&(cc.time_limit as _)),
             memory_limit: *data.0.memory_limit.get_new(&(cc.memory_limit as _)),
             static_tests: *data.0.static_tests.get_new(&(cc.static_tests as _)),
+            test_data: &get_test_data(&***db, cc.subtask_id).await?,
             random_tests: *data.0.random_tests.get_new(&(cc.random_tests as _)),
+            test_concurrency: self.config.challenges.coding_challenges.test_concurrency,
+            scoring_mode: *data.0.scoring_mode.get_new(&cc.scoring_mode),
+            on_progress: None,
+            verdict_cache: None,
         })
         .await?
         {
             return Ok(_CheckError::Response::from(result).into());
         }
+        let starter_code = serde_json::from_value(cc.starter_code.clone()).unwrap_or_default();
+        let starter_code = data.0.starter_code.get_new(&starter_code);
+        match self.check_starter_code(&judge, starter_code).await? {
+            Ok(()) => {}
+            Err(StarterCodeError::EnvironmentNotFound(environment)) => {
+                return UpdateCodingChallenge::starter_code_environment_not_found(environment)
+            }
+            Err(StarterCodeError::Rejected(reason)) => {
+                return UpdateCodingChallenge::starter_code_rejected(reason)
+            }
+            Err(StarterCodeError::Check(result)) => {
+                return Ok(_CheckError::Response::from(*result).into())
+            }
+        }
+
+        if let Some(id) = evaluator_id {
+            challenges_coding_challenge_evaluators::ActiveModel {
+                id: Set(id),
+                challenge_id: Set(cc.subtask_id),
+                creator: Set(auth.0.id),
+                creation_timestamp: Set(Utc::now().naive_utc()),
+                evaluator: Set(evaluator.clone()),
+                environment: Set(evaluator_environment.clone()),
+            }
+            .insert(&***db)
+            .await?;
+        }
 
         let cc = challenges_coding_challenges::ActiveModel {
             subtask_id: Unchanged(cc.subtask_id),
@@ -367,15 +506,35 @@ impl Api {
             memory_limit: data.0.memory_limit.map(|x| x as _).update(cc.memory_limit),
             static_tests: data.0.static_tests.map(|x| x as _).update(cc.static_tests),
             random_tests: data.0.random_tests.map(|x| x as _).update(cc.random_tests),
+            scoring_mode: data.0.scoring_mode.update(cc.scoring_mode),
+            checker: data.0.checker.update(cc.checker),
             evaluator: data.0.evaluator.update(cc.evaluator),
+            evaluator_id: match evaluator_id {
+                Some(id) => Set(id),
+                None => Unchanged(cc.evaluator_id),
+            },
+            evaluator_environment: data
+                .0
+                .evaluator_environment
+                .update(cc.evaluator_environment),
             description: data.0.description.update(cc.description),
             solution_environment: data.0.solution_environment.update(cc.solution_environment),
             solution_code: data.0.solution_code.update(cc.solution_code),
+            starter_code: data
+                .0
+                .starter_code
+                .map(Value::from_iter)
+                .update(cc.starter_code),
         }
         .update(&***db)
         .await?;
 
-        UpdateCodingChallenge::ok(CodingChallenge::from(cc, subtask))
+        UpdateCodingChallenge::ok(CodingChallenge::from(
+            cc,
+            subtask,
+            &self.config.challenges.coding_challenges,
+            &config,
+        ))
     }
 }
 
@@ -431,6 +590,12 @@ response!(CreateCodingChallenge = {
     TimeLimitExceeded(403, error) => u64,
     /// Memory limit exceeded
     MemoryLimitExceeded(403, error) => u64,
+    /// There is no evaluator library for the evaluator environment.
+    EvaluatorEnvironmentNotFound(404, error),
+    /// Starter code has been provided for an environment that does not exist.
+    StarterCodeEnvironmentNotFound(404, error) => String,
+    /// The evaluator rejected the starter code. `details` contains the reason.
+    StarterCodeRejected(400, error) => String,
     .._CheckError::Response,
 });
 
@@ -444,15 +609,61 @@ response!(UpdateCodingChallenge = {
     TimeLimitExceeded(403, error) => u64,
     /// Memory limit exceeded
     MemoryLimitExceeded(403, error) => u64,
+    /// There is no evaluator library for the evaluator environment.
+    EvaluatorEnvironmentNotFound(404, error),
+    /// Starter code has been provided for an environment that does not exist.
+    StarterCodeEnvironmentNotFound(404, error) => String,
+    /// The evaluator rejected the starter code. `details` contains the reason.
+    StarterCodeRejected(400, error) => String,
     .._CheckError::Response,
 });
 
+enum StarterCodeError {
+    EnvironmentNotFound(String),
+    Rejected(String),
+    Check(Box<CheckError>),
+}
+
 impl Api {
-    fn get_judge<'a>(&'a self, evaluator: &'a str) -> Judge<'a> {
-        Judge {
-            sandkasten: &self.sandkasten,
-            evaluator,
-            cache: &self.judge_cache,
+    /// Check that all starter code has been written for existing environments
+    /// and is accepted by the evaluator.
+    async fn check_starter_code(
+        &self,
+        judge: &Judge<'_>,
+        starter_code: &HashMap<String, String>,
+    ) -> Result<Result<(), StarterCodeError>, ErrorResponse> {
+        let environments = self
+            .judge_cache
+            .cached_result(key!(), &[], None, || async {
+                self.sandkasten.list_environments().await
+            })
+            .await??;
+        for (environment, code) in starter_code {
+            if !environments.contains_key(environment) {
+                return Ok(Err(StarterCodeError::EnvironmentNotFound(
+                    environment.clone(),
+                )));
+            }
+            match judge.check_starter_code(environment, code).await {
+                Ok(None) => {}
+                Ok(Some(reason)) => {
+                    return Ok(Err(StarterCodeError::Rejected(format!(
+                        "{environment}: {reason}"
+                    ))))
+                }
+                Err(judge::Error::EvaluatorFailed(err)) => {
+                    return Ok(Err(StarterCodeError::Check(Box::new(
+                        CheckError::EvaluatorFailed(err),
+                    ))))
+                }
+                Err(judge::Error::InvalidOutput(err)) => {
+                    return Ok(Err(StarterCodeError::Check(Box::new(
+                        CheckError::InvalidOutput(err),
+                    ))))
+                }
+                Err(err) => return Err(err.into()),
+            }
         }
+        Ok(Ok(()))
     }
 }
@@ -0,0 +1,328 @@
+use std::sync::Arc;
+
+use entity::{challenges_coding_challenge_evaluators, challenges_coding_challenges};
+use fnct::format::JsonFormatter;
+use lib::{auth::VerifiedUserAuth, config::Config, Cache};
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{
+    param::{Path, Query},
+    OpenApi,
+};
+use sandkasten_client::SandkastenClient;
+use schemas::challenges::coding_challenges::{CodingChallenge, EvaluatorDiff, EvaluatorRevision};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseTransaction, DbErr, EntityTrait, ModelTrait,
+    QueryFilter, QueryOrder, Set, Unchanged,
+};
+use similar::TextDiff;
+use uuid::Uuid;
+
+use super::{_CheckError, check_challenge, test_data::get_test_data, CheckChallenge};
+use crate::{
+    endpoints::Tags,
+    services::{
+        judge::{get_executor_config, Checker, Judge},
+        subtasks::{query_subtask_admin, QuerySubtaskAdminError},
+    },
+};
+
+pub struct Api {
+    pub sandkasten: SandkastenClient,
+    pub judge_cache: Cache<JsonFormatter>,
+    pub config: Arc<Config>,
+}
+
+#[OpenApi(tag = "Tags::CodingChallenges")]
+impl Api {
+    /// List all revisions of the evaluator of a coding challenge, starting
+    /// with the most recent one.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/evaluators",
+        method = "get"
+    )]
+    async fn list_evaluator_revisions(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ListEvaluatorRevisions::Response<VerifiedUserAuth> {
+        let cc = match query_subtask_admin::<challenges_coding_challenges::Entity, _>(
+            &db,
+            &auth.0,
+            task_id.0,
+            subtask_id.0,
+            |cc, _| cc,
+        )
+        .await?
+        {
+            Ok(cc) => cc,
+            Err(QuerySubtaskAdminError::NotFound) => {
+                return ListEvaluatorRevisions::subtask_not_found()
+            }
+            Err(QuerySubtaskAdminError::NoAccess) => return ListEvaluatorRevisions::forbidden(),
+        };
+
+        let revisions = cc
+            .find_related(challenges_coding_challenge_evaluators::Entity)
+            .order_by_desc(challenges_coding_challenge_evaluators::Column::CreationTimestamp)
+            .all(&***db)
+            .await?;
+        ListEvaluatorRevisions::ok(
+            revisions
+                .into_iter()
+                .map(|revision| EvaluatorRevision::from(revision, cc.evaluator_id))
+                .collect(),
+        )
+    }
+
+    /// Get the evaluator of a specific revision.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/evaluators/:evaluator_id",
+        method = "get"
+    )]
+    async fn get_evaluator_revision(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        evaluator_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetEvaluatorRevision::Response<VerifiedUserAuth> {
+        match query_subtask_admin::<challenges_coding_challenges::Entity, _>(
+            &db,
+            &auth.0,
+            task_id.0,
+            subtask_id.0,
+            |cc, _| cc,
+        )
+        .await?
+        {
+            Ok(_) => {}
+            Err(QuerySubtaskAdminError::NotFound) => {
+                return GetEvaluatorRevision::subtask_not_found()
+            }
+            Err(QuerySubtaskAdminError::NoAccess) => return GetEvaluatorRevision::forbidden(),
+        };
+
+        match get_revision(&db, subtask_id.0, evaluator_id.0).await? {
+            Some(revision) => GetEvaluatorRevision::ok(revision.evaluator),
+            None => GetEvaluatorRevision::revision_not_found(),
+        }
+    }
+
+    /// Compare a revision of the evaluator to another one.
+    ///
+    /// If no base revision is specified, the revision is compared to the
+    /// revision preceding it.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/evaluators/:evaluator_id/diff",
+        method = "get"
+    )]
+    async fn get_evaluator_diff(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        evaluator_id: Path<Uuid>,
+        /// The revision to compare against.
+        base: Query<Option<Uuid>>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetEvaluatorDiff::Response<VerifiedUserAuth> {
+        match query_subtask_admin::<challenges_coding_challenges::Entity, _>(
+            &db,
+            &auth.0,
+            task_id.0,
+            subtask_id.0,
+            |cc, _| cc,
+        )
+        .await?
+        {
+            Ok(_) => {}
+            Err(QuerySubtaskAdminError::NotFound) => return GetEvaluatorDiff::subtask_not_found(),
+            Err(QuerySubtaskAdminError::NoAccess) => return GetEvaluatorDiff::forbidden(),
+        };
+
+        let Some(revision) = get_revision(&db, subtask_id.0, evaluator_id.0).await? else {
+            return GetEvaluatorDiff::revision_not_found();
+        };
+        let base = match base.0 {
+            Some(base) => match get_revision(&db, subtask_id.0, base).await? {
+                Some(base) => Some(base),
+                None => return GetEvaluatorDiff::base_not_found(),
+            },
+            None => {
+                challenges_coding_challenge_evaluators::Entity::find()
+                    .filter(
+                        challenges_coding_challenge_evaluators::Column::ChallengeId
+                            .eq(subtask_id.0),
+                    )
+                    .filter(
+                        challenges_coding_challenge_evaluators::Column::CreationTimestamp
+                            .lt(revision.creation_timestamp),
+                    )
+                    .order_by_desc(
+                        challenges_coding_challenge_evaluators::Column::CreationTimestamp,
+                    )
+                    .one(&***db)
+                    .await?
+            }
+        };
+
+        let old = base
+            .as_ref()
+            .map(|base| base.evaluator.as_str())
+            .unwrap_or_default();
+        let old_header = base
+            .as_ref()
+            .map(|base| base.id.to_string())
+            .unwrap_or_else(|| "/dev/null".into());
+        let diff = TextDiff::from_lines(old, &revision.evaluator)
+            .unified_diff()
+            .header(&old_header, &revision.id.to_string())
+            .to_string();
+
+        GetEvaluatorDiff::ok(EvaluatorDiff {
+            base: base.map(|base| base.id),
+            revision: revision.id,
+            diff,
+        })
+    }
+
+    /// Make a previous revision the current evaluator of a coding challenge.
+    ///
+    /// The evaluator is checked against the sample solution before it is
+    /// restored.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/evaluators/:evaluator_id/restore",
+        method = "post"
+    )]
+    async fn restore_evaluator_revision(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        evaluator_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> RestoreEvaluatorRevision::Response<VerifiedUserAuth> {
+        let (cc, subtask) = match query_subtask_admin::<challenges_coding_challenges::Entity, _>(
+            &db,
+            &auth.0,
+            task_id.0,
+            subtask_id.0,
+            |cc, subtask| (cc, subtask),
+        )
+        .await?
+        {
+            Ok(x) => x,
+            Err(QuerySubtaskAdminError::NotFound) => {
+                return RestoreEvaluatorRevision::subtask_not_found()
+            }
+            Err(QuerySubtaskAdminError::NoAccess) => return RestoreEvaluatorRevision::forbidden(),
+        };
+
+        let Some(revision) = get_revision(&db, subtask_id.0, evaluator_id.0).await? else {
+            return RestoreEvaluatorRevision::revision_not_found();
+        };
+
+        if let Err(result) = check_challenge(CheckChallenge {
+            judge: Judge::with_evaluator(
+                &self.sandkasten,
+                &self.judge_cache,
+                &self.config.challenges.coding_challenges,
+                revision.id,
+                &revision.environment,
+                &revision.evaluator,
+                Checker::from(&cc),
+            ),
+            challenge_id: cc.subtask_id,
+            solution_environment: &cc.solution_environment,
+            solution_code: &cc.solution_code,
+            solution_files: &[],
+            time_limit: cc.time_limit as _,
+            memory_limit: cc.memory_limit as _,
+            static_tests: cc.static_tests as _,
+            test_data: &get_test_data(&***db, cc.subtask_id).await?,
+            random_tests: cc.random_tests as _,
+            test_concurrency: self.config.challenges.coding_challenges.test_concurrency,
+            scoring_mode: cc.scoring_mode,
+            on_progress: None,
+            verdict_cache: None,
+        })
+        .await?
+        {
+            return Ok(_CheckError::Response::from(result).into());
+        }
+
+        let cc = challenges_coding_challenges::ActiveModel {
+            subtask_id: Unchanged(cc.subtask_id),
+            evaluator: Set(revision.evaluator),
+            evaluator_id: Set(revision.id),
+            evaluator_environment: Set(revision.environment),
+            ..Default::default()
+        }
+        .update(&***db)
+        .await?;
+
+        let executor = get_executor_config(&self.judge_cache, &self.sandkasten).await?;
+        RestoreEvaluatorRevision::ok(CodingChallenge::from(
+            cc,
+            subtask,
+            &self.config.challenges.coding_challenges,
+            &executor,
+        ))
+    }
+}
+
+response!(ListEvaluatorRevisions = {
+    Ok(200) => Vec<EvaluatorRevision>,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// The user is not allowed to request the evaluator of this coding challenge.
+    Forbidden(403, error),
+});
+
+response!(GetEvaluatorRevision = {
+    Ok(200) => String,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// Evaluator revision does not exist.
+    RevisionNotFound(404, error),
+    /// The user is not allowed to request the evaluator of this coding challenge.
+    Forbidden(403, error),
+});
+
+response!(GetEvaluatorDiff = {
+    Ok(200) => EvaluatorDiff,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// Evaluator revision does not exist.
+    RevisionNotFound(404, error),
+    /// Base revision does not exist.
+    BaseNotFound(404, error),
+    /// The user is not allowed to request the evaluator of this coding challenge.
+    Forbidden(403, error),
+});
+
+response!(RestoreEvaluatorRevision = {
+    Ok(200) => CodingChallenge,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// Evaluator revision does not exist.
+    RevisionNotFound(404, error),
+    /// The user is not allowed to modify the evaluator of this coding challenge.
+    Forbidden(403, error),
+    .._CheckError::Response,
+});
+
+async fn get_revision(
+    db: &DatabaseTransaction,
+    challenge_id: Uuid,
+    evaluator_id: Uuid,
+) -> Result<Option<challenges_coding_challenge_evaluators::Model>, DbErr> {
+    challenges_coding_challenge_evaluators::Entity::find_by_id(evaluator_id)
+        .filter(challenges_coding_challenge_evaluators::Column::ChallengeId.eq(challenge_id))
+        .one(db)
+        .await
+}
@@ -1,24 +1,41 @@
 use std::sync::Arc;
 
-use entity::challenges_coding_challenges;
+use entity::{challenges_coding_challenges, sea_orm_active_enums::ChallengesChecker};
 use fnct::{format::JsonFormatter, key};
-use lib::{auth::VerifiedUserAuth, config::Config, Cache, SharedState};
+use lib::{
+    auth::{User, VerifiedUserAuth},
+    config::Config,
+    Cache, SharedState,
+};
 use poem::web::Data;
 use poem_ext::{db::DbTxn, response};
 use poem_openapi::{param::Path, payload::Json, OpenApi};
 use sandkasten_client::{
-    schemas::{environments::ListEnvironmentsResponse, programs::RunResult},
-    SandkastenClient,
+    schemas::{
+        environments::ListEnvironmentsResponse,
+        programs::{BuildError, RunResult},
+        ErrorResponse,
+    },
+    Error as SandkastenError, SandkastenClient,
+};
+use schemas::challenges::coding_challenges::{
+    CheckResult, DebugCheckRequest, DebugEvaluatorRequest, DebugEvaluatorResult,
+    DebugGenerateRequest, DebugPrepareRequest, ExecutorConfig, RunCodeRequest, RunCodeResult,
+    SubmissionContent,
 };
-use schemas::challenges::coding_challenges::{CheckResult, ExecutorConfig, SubmissionContent};
+use sea_orm::DatabaseTransaction;
 use tracing::error;
 use uuid::Uuid;
 
 use crate::{
     endpoints::Tags,
     services::{
-        judge::{self, get_executor_config, Judge},
-        subtasks::{check_hearts, get_subtask},
+        judge::{
+            self, get_evaluator_assets, get_executor_config, Checker, EvaluatorCommand, Judge,
+        },
+        rate_limit::RateLimit,
+        subtasks::{can_create, check_hearts, get_subtask},
+        tasks::get_task_with_specific,
     },
 };
 
@@ -27,6 +44,7 @@ pub struct Api {
     pub config: Arc<Config>,
     pub sandkasten: SandkastenClient,
     pub judge_cache: Cache<JsonFormatter>,
+    pub rate_limit: RateLimit,
 }
 
 #[OpenApi(tag = "Tags::CodingChallenges")]
@@ -59,7 +77,12 @@ impl Api {
             return TestExample::not_enough_hearts();
         }
 
-        let judge = self.get_judge(&cc.evaluator);
+        let judge = Judge::new(
+            &self.sandkasten,
+            &self.judge_cache,
+            &self.config.challenges.coding_challenges,
+            &cc,
+        );
 
         let examples = match judge.examples().await {
             Err(judge::Error::EvaluatorFailed(err) | judge::Error::InvalidOutput(err)) => {
@@ -93,6 +116,7 @@ impl Api {
                 &inp,
                 &data.0.environment,
                 &data.0.code,
+                &data.0.files,
                 Some(cc.time_limit as _),
                 Some(cc.memory_limit as _),
             )
@@ -115,6 +139,146 @@ impl Api {
         TestExample::ok(result)
     }
 
+    /// Run a solution on custom input, using the limits of the coding
+    /// challenge.
+    ///
+    /// The output is not checked by the evaluator and no hearts are consumed.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/run",
+        method = "post"
+    )]
+    async fn run_code(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        data: Json<RunCodeRequest>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> RunCode::Response<VerifiedUserAuth> {
+        let Some((cc, subtask)) =
+            get_subtask::<challenges_coding_challenges::Entity>(&db, task_id.0, subtask_id.0)
+                .await?
+        else {
+            return RunCode::subtask_not_found();
+        };
+        if !auth.0.admin && auth.0.id != subtask.creator && !subtask.enabled {
+            return RunCode::subtask_not_found();
+        }
+
+        if let Some(time_left) = self
+            .rate_limit
+            .acquire(
+                &format!("coding_challenges:run:{}", auth.0.id),
+                self.config.challenges.coding_challenges.run_timeout,
+            )
+            .await?
+        {
+            return RunCode::too_many_requests(time_left);
+        }
+
+        let judge = Judge::new(
+            &self.sandkasten,
+            &self.judge_cache,
+            &self.config.challenges.coding_challenges,
+            &cc,
+        );
+        let result = match judge
+            .run_code(
+                &data.0.content.environment,
+                &data.0.content.code,
+                &data.0.content.files,
+                &data.0.stdin,
+                Some(cc.time_limit as _),
+                Some(cc.memory_limit as _),
+            )
+            .await
+        {
+            Err(judge::Error::EnvironmentNotFound) => return RunCode::environment_not_found(),
+            x => x?,
+        };
+
+        RunCode::ok(result)
+    }
+
+    /// Run the `examples` command of a draft evaluator without saving it.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/evaluator/examples",
+        method = "post"
+    )]
+    async fn debug_evaluator_examples(
+        &self,
+        task_id: Path<Uuid>,
+        data: Json<DebugEvaluatorRequest>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> DebugEvaluator::Response<VerifiedUserAuth> {
+        self.debug_evaluator(&db, &auth.0, task_id.0, &data.0, EvaluatorCommand::Examples)
+            .await
+    }
+
+    /// Run the `generate` command of a draft evaluator without saving it.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/evaluator/generate",
+        method = "post"
+    )]
+    async fn debug_evaluator_generate(
+        &self,
+        task_id: Path<Uuid>,
+        data: Json<DebugGenerateRequest>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> DebugEvaluator::Response<VerifiedUserAuth> {
+        let command = EvaluatorCommand::Generate { seed: &data.0.seed };
+        self.debug_evaluator(&db, &auth.0, task_id.0, &data.0.evaluator, command)
+            .await
+    }
+
+    /// Run the `prepare` command of a draft evaluator on the given solution
+    /// without saving the evaluator.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/evaluator/prepare",
+        method = "post"
+    )]
+    async fn debug_evaluator_prepare(
+        &self,
+        task_id: Path<Uuid>,
+        data: Json<DebugPrepareRequest>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> DebugEvaluator::Response<VerifiedUserAuth> {
+        let command = EvaluatorCommand::Prepare {
+            seed: &data.0.seed,
+            environment: &data.0.solution.environment,
+            code: &data.0.solution.code,
+            files: &data.0.solution.files,
+            data: &data.0.data,
+        };
+        self.debug_evaluator(&db, &auth.0, task_id.0, &data.0.evaluator, command)
+            .await
+    }
+
+    /// Run the `check` command of a draft evaluator on the given output
+    /// without saving the evaluator.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/evaluator/check",
+        method = "post"
+    )]
+    async fn debug_evaluator_check(
+        &self,
+        task_id: Path<Uuid>,
+        data: Json<DebugCheckRequest>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> DebugEvaluator::Response<VerifiedUserAuth> {
+        let command = EvaluatorCommand::Check {
+            seed: &data.0.seed,
+            output: &data.0.output,
+            data: &data.0.data,
+        };
+        self.debug_evaluator(&db, &auth.0, task_id.0, &data.0.evaluator, command)
+            .await
+    }
+
     /// Return a map of all environments available on the code execution engine.
     ///
     /// The keys represent the environment ids and the values contain additional
@@ -136,7 +300,18 @@ impl Api {
     /// Return the config of the code execution engine.
     #[oai(path = "/executor/config", method = "get")]
     async fn get_config(&self, _auth: VerifiedUserAuth) -> GetConfig::Response<VerifiedUserAuth> {
-        GetConfig::ok(get_executor_config(&self.judge_cache, &self.sandkasten).await?)
+        let config = get_executor_config(&self.judge_cache, &self.sandkasten).await?;
+        GetConfig::ok(ExecutorConfig {
+            limit_multipliers: self
+                .config
+                .challenges
+                .coding_challenges
+                .limit_multipliers
+                .iter()
+                .map(|(environment, &multipliers)| (environment.clone(), multipliers.into()))
+                .collect(),
+            ..config
+        })
     }
 }
 
@@ -152,6 +327,28 @@ response!(TestExample = {
     EvaluatorFailed(400, error),
 });
 
+response!(RunCode = {
+    Ok(200) => RunCodeResult,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// Environment does not exist.
+    EnvironmentNotFound(404, error),
+    /// Try again later. `details` contains the number of seconds to wait.
+    TooManyRequests(429, error) => u64,
+});
+
+response!(DebugEvaluator = {
+    Ok(200) => DebugEvaluatorResult,
+    /// Task does not exist.
+    TaskNotFound(404, error),
+    /// The user is not allowed to create coding challenges in this task.
+    Forbidden(403, error),
+    /// The evaluator environment does not exist.
+    EvaluatorEnvironmentNotFound(404, error),
+    /// Try again later. `details` contains the number of seconds to wait.
+    TooManyRequests(429, error) => u64,
+});
+
 response!(ListEnvironments = {
     /// Map of available environments.
     Ok(200) => ListEnvironmentsResponse,
@@ -163,11 +360,73 @@ response!(GetConfig = {
 });
 
 impl Api {
-    fn get_judge<'a>(&'a self, evaluator: &'a str) -> Judge<'a> {
-        Judge {
-            sandkasten: &self.sandkasten,
-            evaluator,
-            cache: &self.judge_cache,
+    async fn debug_evaluator(
+        &self,
+        db: &DatabaseTransaction,
+        user: &User,
+        task_id: Uuid,
+        evaluator: &DebugEvaluatorRequest,
+        command: EvaluatorCommand<'_>,
+    ) -> DebugEvaluator::Response<VerifiedUserAuth> {
+        let Some((_, task)) = get_task_with_specific(db, task_id).await? else {
+            return DebugEvaluator::task_not_found();
+        };
+        if !can_create(&self.state.services, &self.config, &task, user).await? {
+            return DebugEvaluator::forbidden();
+        }
+        if get_evaluator_assets(&evaluator.evaluator_environment).is_none() {
+            return DebugEvaluator::evaluator_environment_not_found();
         }
+
+        if let Some(time_left) = self
+            .rate_limit
+            .acquire(
+                &format!("coding_challenges:debug_evaluator:{}", user.id),
+                self.config.challenges.coding_challenges.run_timeout,
+            )
+            .await?
+        {
+            return DebugEvaluator::too_many_requests(time_left);
+        }
+
+        // the output of the evaluator is not interpreted, so the checker is
+        // never used
+        let judge = Judge::with_evaluator(
+            &self.sandkasten,
+            &self.judge_cache,
+            &self.config.challenges.coding_challenges,
+            Uuid::new_v4(),
+            &evaluator.evaluator_environment,
+            &evaluator.evaluator,
+            Checker {
+                mode: ChallengesChecker::Evaluator,
+                reference_environment: &evaluator.evaluator_environment,
+                reference_code: "",
+            },
+        );
+        let (compile, run) = match judge.debug_evaluator(command).await {
+            Err(judge::Error::Build(SandkastenError::ErrorResponse(err))) => match *err {
+                ErrorResponse::Inner(BuildError::CompileError(result)) => (Some(result), None),
+                ErrorResponse::Inner(BuildError::EnvironmentNotFound) => {
+                    return DebugEvaluator::evaluator_environment_not_found();
+                }
+                err => Err(judge::Error::Build(SandkastenError::ErrorResponse(
+                    Box::new(err),
+                )))?,
+            },
+            x => {
+                let result = x?;
+                (result.build, Some(result.run))
+            }
+        };
+        let output = run
+            .as_ref()
+            .and_then(|run| serde_json::from_str(&run.stdout).ok());
+
+        DebugEvaluator::ok(DebugEvaluatorResult {
+            compile,
+            run,
+            output,
+        })
     }
 }
@@ -1,29 +1,42 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
+use entity::{
+    challenges_coding_challenge_test_data,
+    sea_orm_active_enums::{ChallengesScoringMode, ChallengesVerdict},
+};
 use fnct::format::JsonFormatter;
+use futures::{stream, StreamExt};
 use lib::{config::Config, Cache, SharedState};
 use poem_ext::response;
 use poem_openapi::{Object, OpenApi};
 use sandkasten_client::{
-    schemas::programs::{BuildRunResult, RunResult},
+    schemas::programs::{BuildRunResult, File, RunResult},
     SandkastenClient,
 };
 use schemas::challenges::coding_challenges::CheckResult;
-use tokio::sync::Semaphore;
 use uuid::Uuid;
 
-use crate::services::judge::{Error as JudgeError, Judge};
+use crate::services::{
+    judge::{test_data_seed, Error as JudgeError, Judge},
+    rate_limit::RateLimit,
+    submission_events::SubmissionEvents,
+};
 
 mod assets;
 mod challenges;
+mod evaluators;
 mod judge;
+mod similarities;
+mod stats;
 pub mod submissions;
+mod test_data;
 
 pub struct CodingChallenges {
     pub state: Arc<SharedState>,
     pub sandkasten: SandkastenClient,
     pub judge_cache: Cache<JsonFormatter>,
-    pub judge_lock: Arc<Semaphore>,
+    pub submission_events: SubmissionEvents,
+    pub rate_limit: RateLimit,
     pub config: Arc<Config>,
 }
 
@@ -37,22 +50,38 @@ impl CodingChallenges {
                 config: Arc::clone(&self.config),
                 state: Arc::clone(&self.state),
             },
+            evaluators::Api {
+                sandkasten: self.sandkasten.clone(),
+                judge_cache: self.judge_cache.clone(),
+                config: Arc::clone(&self.config),
+            },
             judge::Api {
                 state: Arc::clone(&self.state),
                 config: Arc::clone(&self.config),
                 sandkasten: self.sandkasten.clone(),
                 judge_cache: self.judge_cache.clone(),
+                rate_limit: self.rate_limit,
+            },
+            test_data::Api {
+                config: Arc::clone(&self.config),
+                sandkasten: self.sandkasten.clone(),
+                judge_cache: self.judge_cache.clone(),
+            },
+            similarities::Api {
+                state: Arc::clone(&self.state),
+                config: Arc::clone(&self.config),
+            }
+            .setup_api(),
+            stats::Api {
+                state: Arc::clone(&self.state),
+                config: Arc::clone(&self.config),
             },
             submissions::Api {
                 config: self.config,
                 state: self.state,
                 sandkasten: self.sandkasten,
                 judge_cache: self.judge_cache,
-                reward_lock: Default::default(),
-                queue_positions: Arc::new(
-                    QueuePositions::new(self.judge_lock.available_permits()).into(),
-                ),
-                judge_lock: self.judge_lock,
+                submission_events: self.submission_events,
             }
             .setup_api()
             .await?,
@@ -60,18 +89,53 @@ impl CodingChallenges {
     }
 }
 
-async fn check_challenge(
+async fn check_challenge(args: CheckChallenge<'_>) -> Result<Result<(), CheckError>, JudgeError> {
+    let testcases = match run_testcases(args).await? {
+        Ok(testcases) => testcases,
+        Err(err) => return Ok(Err(err)),
+    };
+
+    Ok(
+        match testcases
+            .into_iter()
+            .find(|testcase| testcase.result.verdict != ChallengesVerdict::Ok)
+        {
+            Some(Testcase { seed, result, .. }) => {
+                Err(CheckError::TestcaseFailed(CheckTestcaseError {
+                    seed,
+                    result,
+                }))
+            }
+            None => Ok(()),
+        },
+    )
+}
+
+/// Run the examples, static tests, fixed test data and random tests of a coding
+/// challenge and return the results of all tests that have been executed. In
+/// the all-or-nothing scoring mode the judge stops after the first failed test.
+/// Up to `test_concurrency` tests are run in parallel. `on_progress` is called
+/// with the number of each test (starting at `1`) and the total number of tests
+/// when the test is started. Results of examples and static tests found in
+/// `verdict_cache` are reused instead of running the tests again.
+async fn run_testcases(
     CheckChallenge {
         judge,
         challenge_id,
         solution_environment,
         solution_code,
+        solution_files,
         time_limit,
         memory_limit,
         static_tests,
+        test_data,
         random_tests,
+        test_concurrency,
+        scoring_mode,
+        on_progress,
+        verdict_cache,
     }: CheckChallenge<'_>,
-) -> Result<Result<(), CheckError>, JudgeError> {
+) -> Result<Result<Vec<Testcase>, CheckError>, JudgeError> {
     let examples = match judge.examples().await {
         Err(JudgeError::EvaluatorFailed(err)) => {
             return Ok(Err(CheckError::EvaluatorFailed(err)));
@@ -85,21 +149,70 @@ async fn check_challenge(
         return Ok(Err(CheckError::NoExamples));
     }
 
-    for seed in examples
-        .into_iter()
-        .chain((0..static_tests).map(|x| format!("_static_{x}_{challenge_id}")))
-        .chain((0..random_tests).map(|_| Uuid::new_v4().to_string()))
-    {
-        let result = match judge
-            .get_example_checked(
-                &seed,
-                solution_environment,
-                solution_code,
-                Some(time_limit),
-                Some(memory_limit),
-            )
-            .await
-        {
+    let judge = &judge;
+    let deterministic = examples.len() + static_tests as usize;
+    let total = deterministic + test_data.len() + random_tests as usize;
+    let mut results = stream::iter(
+        examples
+            .into_iter()
+            .chain((0..static_tests).map(|x| format!("_static_{x}_{challenge_id}")))
+            .map(|seed| (seed, None))
+            .chain((0..test_data.len()).map(|x| (test_data_seed(test_data[x].id), Some(x))))
+            .chain((0..random_tests).map(|_| (Uuid::new_v4().to_string(), None)))
+            .enumerate(),
+    )
+    .map(|(i, (seed, test))| async move {
+        if let Some(on_progress) = on_progress {
+            on_progress(i + 1, total);
+        }
+        let result = async {
+            if let Some(test) = test {
+                let result = judge
+                    .run_test_data(
+                        &test_data[test],
+                        solution_environment,
+                        solution_code,
+                        solution_files,
+                        Some(time_limit),
+                        Some(memory_limit),
+                    )
+                    .await?;
+                return Ok((result, 1.0));
+            }
+            let cached = verdict_cache
+                .filter(|_| i < deterministic)
+                .and_then(|cache| cache.get(&seed));
+            let result = match cached {
+                Some(result) => result.clone(),
+                None => {
+                    judge
+                        .run_test(
+                            &seed,
+                            solution_environment,
+                            solution_code,
+                            solution_files,
+                            Some(time_limit),
+                            Some(memory_limit),
+                        )
+                        .await?
+                }
+            };
+            let weight = match scoring_mode {
+                ChallengesScoringMode::Weighted => {
+                    judge.generate(&seed).await?.weight.unwrap_or(1.0).max(0.0)
+                }
+                _ => 1.0,
+            };
+            Ok((result, weight))
+        }
+        .await;
+        (i, seed, result)
+    })
+    .buffered(test_concurrency.max(1));
+
+    let mut testcases = Vec::new();
+    while let Some((i, seed, result)) = results.next().await {
+        let (result, weight) = match result {
             Err(JudgeError::EnvironmentNotFound) => {
                 return Ok(Err(CheckError::EnvironmentNotFound));
             }
@@ -111,15 +224,19 @@ async fn check_challenge(
             }
             x => x?,
         };
-        if let Err(result) = result {
-            return Ok(Err(CheckError::TestcaseFailed(CheckTestcaseError {
-                seed: seed.clone(),
-                result,
-            })));
+        let failed = result.verdict != ChallengesVerdict::Ok;
+        testcases.push(Testcase {
+            seed,
+            result,
+            weight,
+            deterministic: i < deterministic,
+        });
+        if failed && scoring_mode == ChallengesScoringMode::AllOrNothing {
+            break;
         }
     }
 
-    Ok(Ok(()))
+    Ok(Ok(testcases))
 }
 
 mod _check_error {
@@ -139,17 +256,23 @@ mod _check_error {
 }
 use _check_error::CheckError::raw as _CheckError;
 
-use self::submissions::QueuePositions;
-
 struct CheckChallenge<'a> {
     judge: Judge<'a>,
     challenge_id: Uuid,
     solution_environment: &'a str,
     solution_code: &'a str,
+    solution_files: &'a [File],
     time_limit: u64,
     memory_limit: u64,
     static_tests: u8,
+    /// The fixed test cases of the coding challenge.
+    test_data: &'a [challenges_coding_challenge_test_data::Model],
     random_tests: u8,
+    test_concurrency: usize,
+    scoring_mode: ChallengesScoringMode,
+    on_progress: Option<&'a (dyn Fn(usize, usize) + Send + Sync)>,
+    /// Previous results of examples and static tests, keyed by seed.
+    verdict_cache: Option<&'a HashMap<String, CheckResult<RunResult>>>,
 }
 
 impl From<CheckError> for _CheckError::Response {
@@ -178,8 +301,98 @@ enum CheckError {
     TestcaseFailed(CheckTestcaseError),
 }
 
+#[derive(Debug)]
+struct Testcase {
+    seed: String,
+    result: CheckResult<RunResult>,
+    weight: f64,
+    /// Whether the result only depends on the submission and the evaluator
+    /// (true for examples and static tests).
+    deterministic: bool,
+}
+
+/// Return the score of a submission between `0` and `1` given the results of
+/// its tests.
+fn score(scoring_mode: ChallengesScoringMode, testcases: &[Testcase]) -> f64 {
+    let passed = |testcase: &Testcase| testcase.result.verdict == ChallengesVerdict::Ok;
+    let total = testcases
+        .iter()
+        .map(|testcase| testcase.weight)
+        .sum::<f64>();
+    if scoring_mode == ChallengesScoringMode::AllOrNothing || total <= 0.0 {
+        return if testcases.iter().all(passed) {
+            1.0
+        } else {
+            0.0
+        };
+    }
+    testcases
+        .iter()
+        .filter(|testcase| passed(testcase))
+        .map(|testcase| testcase.weight)
+        .sum::<f64>()
+        / total
+}
+
 #[derive(Debug, Object)]
 pub struct CheckTestcaseError {
     pub seed: String,
     pub result: CheckResult<RunResult>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn testcase(verdict: ChallengesVerdict, weight: f64) -> Testcase {
+        Testcase {
+            seed: String::new(),
+            result: CheckResult {
+                verdict,
+                reason: None,
+                compile: None,
+                run: None,
+            },
+            weight,
+            deterministic: false,
+        }
+    }
+
+    #[test]
+    fn score_all_or_nothing() {
+        use ChallengesScoringMode::AllOrNothing;
+        use ChallengesVerdict::{Ok, WrongAnswer};
+        assert_eq!(
+            score(AllOrNothing, &[testcase(Ok, 1.0), testcase(Ok, 1.0)]),
+            1.0
+        );
+        assert_eq!(
+            score(
+                AllOrNothing,
+                &[testcase(Ok, 1.0), testcase(WrongAnswer, 1.0)]
+            ),
+            0.0
+        );
+    }
+
+    #[test]
+    fn score_partial() {
+        use ChallengesScoringMode::{Fraction, Weighted};
+        use ChallengesVerdict::{Ok, TimeLimitExceeded, WrongAnswer};
+        let testcases = [
+            testcase(Ok, 1.0),
+            testcase(WrongAnswer, 1.0),
+            testcase(Ok, 1.0),
+            testcase(TimeLimitExceeded, 1.0),
+        ];
+        assert_eq!(score(Fraction, &testcases), 0.5);
+        let testcases = [
+            testcase(Ok, 3.0),
+            testcase(WrongAnswer, 1.0),
+            testcase(Ok, 0.0),
+        ];
+        assert_eq!(score(Weighted, &testcases), 0.75);
+        assert_eq!(score(Weighted, &[testcase(Ok, 0.0)]), 1.0);
+        assert_eq!(score(Weighted, &[testcase(WrongAnswer, 0.0)]), 0.0);
+    }
+}
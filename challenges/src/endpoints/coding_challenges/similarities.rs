@@ -0,0 +1,170 @@
+use std::{sync::Arc, time::Duration};
+
+use entity::{challenges_coding_challenge_similarities, sea_orm_active_enums::ChallengesBanAction};
+use lib::{auth::AdminAuth, config::Config, SharedState};
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{
+    param::{Path, Query},
+    payload::Json,
+    OpenApi,
+};
+use schemas::challenges::coding_challenges::{
+    ResolveSimilarityAction, ResolveSimilarityRequest, Similarity,
+};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect, Set, Unchanged,
+};
+use tracing::{debug, error};
+use uuid::Uuid;
+
+use crate::{
+    endpoints::Tags,
+    services::{plagiarism::detect_new_similarities, subtasks::ban_user},
+};
+
+pub struct Api {
+    pub state: Arc<SharedState>,
+    pub config: Arc<Config>,
+}
+
+#[OpenApi(tag = "Tags::CodingChallenges")]
+impl Api {
+    /// Return a list of pairs of suspiciously similar submissions, starting
+    /// with the most similar ones.
+    #[oai(path = "/coding_challenges/similarities", method = "get")]
+    async fn list_similarities(
+        &self,
+        /// Only return pairs of submissions of this coding challenge.
+        challenge_id: Query<Option<Uuid>>,
+        /// Only return resolved or unresolved pairs of submissions.
+        resolved: Query<Option<bool>>,
+        /// Maximum number of pairs to return
+        limit: Query<Option<u64>>,
+        /// Pagination offset
+        offset: Query<Option<u64>>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> ListSimilarities::Response<AdminAuth> {
+        let mut query = challenges_coding_challenge_similarities::Entity::find();
+        if let Some(challenge_id) = challenge_id.0 {
+            query = query.filter(
+                challenges_coding_challenge_similarities::Column::ChallengeId.eq(challenge_id),
+            );
+        }
+        if let Some(resolved) = resolved.0 {
+            query = query
+                .filter(challenges_coding_challenge_similarities::Column::Resolved.eq(resolved));
+        }
+        ListSimilarities::ok(
+            query
+                .order_by_desc(challenges_coding_challenge_similarities::Column::Similarity)
+                .order_by_desc(challenges_coding_challenge_similarities::Column::CreationTimestamp)
+                .limit(limit.0)
+                .offset(offset.0)
+                .all(&***db)
+                .await?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        )
+    }
+
+    /// Resolve a pair of suspiciously similar submissions.
+    ///
+    /// Users who are blocked are banned from submitting solutions to coding
+    /// challenges.
+    #[oai(
+        path = "/coding_challenges/similarities/:similarity_id",
+        method = "put"
+    )]
+    async fn resolve_similarity(
+        &self,
+        similarity_id: Path<Uuid>,
+        data: Json<ResolveSimilarityRequest>,
+        db: Data<&DbTxn>,
+        auth: AdminAuth,
+    ) -> ResolveSimilarity::Response<AdminAuth> {
+        let Some(similarity) =
+            challenges_coding_challenge_similarities::Entity::find_by_id(similarity_id.0)
+                .one(&***db)
+                .await?
+        else {
+            return ResolveSimilarity::similarity_not_found();
+        };
+
+        let blocked = match data.0.action {
+            ResolveSimilarityAction::Dismiss => vec![],
+            ResolveSimilarityAction::BlockFirst => {
+                vec![(similarity.user_a, similarity.submission_a)]
+            }
+            ResolveSimilarityAction::BlockSecond => {
+                vec![(similarity.user_b, similarity.submission_b)]
+            }
+            ResolveSimilarityAction::BlockBoth => vec![
+                (similarity.user_a, similarity.submission_a),
+                (similarity.user_b, similarity.submission_b),
+            ],
+        };
+        for (user_id, submission_id) in blocked {
+            ban_user(
+                &db,
+                user_id,
+                ChallengesBanAction::Submit,
+                &self.config.challenges.coding_challenges.ban_days,
+                auth.0.id,
+                format!(
+                    "Plagiarized submission {submission_id} ({:.0}% similarity)",
+                    similarity.similarity * 100.0
+                ),
+            )
+            .await?;
+        }
+
+        let similarity = challenges_coding_challenge_similarities::ActiveModel {
+            id: Unchanged(similarity.id),
+            resolved: Set(true),
+            ..Default::default()
+        }
+        .update(&***db)
+        .await?;
+        ResolveSimilarity::ok(similarity.into())
+    }
+}
+
+response!(ListSimilarities = {
+    Ok(200) => Vec<Similarity>,
+});
+
+response!(ResolveSimilarity = {
+    Ok(200) => Similarity,
+    /// The pair of submissions does not exist.
+    SimilarityNotFound(404, error),
+});
+
+impl Api {
+    pub fn setup_api(self) -> Self {
+        tokio::spawn(detect_similarities_periodically(
+            self.state.db.clone(),
+            Arc::clone(&self.config),
+        ));
+        self
+    }
+}
+
+/// Periodically compare new accepted submissions to all other accepted
+/// submissions of the same coding challenge.
+async fn detect_similarities_periodically(db: DatabaseConnection, config: Arc<Config>) {
+    let config = &config.challenges.coding_challenges;
+    let mut interval =
+        tokio::time::interval(Duration::from_secs(config.similarity_interval.max(1)));
+    loop {
+        interval.tick().await;
+        match detect_new_similarities(&db, config.similarity_threshold).await {
+            Ok(Some(found)) => debug!("found {found} pair(s) of similar submissions"),
+            Ok(None) => debug!("similar submissions are already being detected"),
+            Err(err) => error!("failed to detect similar submissions: {err}"),
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use entity::{
+    challenges_coding_challenges, challenges_subtasks, sea_orm_active_enums::ChallengesSubtaskType,
+};
+use lib::{auth::VerifiedUserAuth, config::Config, SharedState};
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{param::Path, OpenApi};
+use schemas::challenges::coding_challenges::CodingChallengeStats;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use uuid::Uuid;
+
+use crate::{
+    endpoints::Tags,
+    services::{
+        stats::{coding_challenge_stats, get_judged_submissions},
+        subtasks::{can_create, get_subtask},
+        tasks::get_task_with_specific,
+    },
+};
+
+pub struct Api {
+    pub state: Arc<SharedState>,
+    pub config: Arc<Config>,
+}
+
+#[OpenApi(tag = "Tags::CodingChallenges")]
+impl Api {
+    /// Return statistics about the submissions of all coding challenges in a
+    /// task.
+    ///
+    /// Only users who are allowed to create coding challenges in the task can
+    /// see these statistics.
+    #[oai(path = "/tasks/:task_id/coding_challenges/stats", method = "get")]
+    async fn get_task_stats(
+        &self,
+        task_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetTaskStats::Response<VerifiedUserAuth> {
+        let Some((_, task)) = get_task_with_specific(&db, task_id.0).await? else {
+            return GetTaskStats::task_not_found();
+        };
+        if !can_create(&self.state.services, &self.config, &task, &auth.0).await? {
+            return GetTaskStats::forbidden();
+        }
+
+        let subtasks = challenges_subtasks::Entity::find()
+            .filter(challenges_subtasks::Column::TaskId.eq(task_id.0))
+            .filter(challenges_subtasks::Column::Ty.eq(ChallengesSubtaskType::CodingChallenge))
+            .all(&***db)
+            .await?;
+        let submissions = get_judged_submissions(&***db, &subtasks).await?;
+        GetTaskStats::ok(coding_challenge_stats(&submissions))
+    }
+
+    /// Return statistics about the submissions of a coding challenge.
+    ///
+    /// Submissions of the creator of the coding challenge are not included.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/stats",
+        method = "get"
+    )]
+    async fn get_challenge_stats(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetChallengeStats::Response<VerifiedUserAuth> {
+        let Some((_, subtask)) =
+            get_subtask::<challenges_coding_challenges::Entity>(&db, task_id.0, subtask_id.0)
+                .await?
+        else {
+            return GetChallengeStats::subtask_not_found();
+        };
+        if !auth.0.admin && auth.0.id != subtask.creator && !subtask.enabled {
+            return GetChallengeStats::subtask_not_found();
+        }
+
+        let submissions = get_judged_submissions(&***db, &[subtask]).await?;
+        GetChallengeStats::ok(coding_challenge_stats(&submissions))
+    }
+}
+
+response!(GetTaskStats = {
+    Ok(200) => CodingChallengeStats,
+    /// Task does not exist.
+    TaskNotFound(404, error),
+    /// The user is not allowed to create coding challenges in this task.
+    Forbidden(403, error),
+});
+
+response!(GetChallengeStats = {
+    Ok(200) => CodingChallengeStats,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+});
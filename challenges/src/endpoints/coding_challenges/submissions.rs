@@ -1,41 +1,71 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use anyhow::{bail, Context};
-use chrono::Utc;
+use anyhow::Context;
+use chrono::{DateTime, Utc};
 use entity::{
-    challenges_coding_challenge_result, challenges_coding_challenge_submissions,
-    challenges_coding_challenges, challenges_subtasks, challenges_user_subtasks,
-    sea_orm_active_enums::ChallengesVerdict,
+    challenges_coding_challenge_judge_jobs, challenges_coding_challenge_result,
+    challenges_coding_challenge_submission_files, challenges_coding_challenge_submissions,
+    challenges_coding_challenge_test_data, challenges_coding_challenge_test_result,
+    challenges_coding_challenge_verdict_cache, challenges_coding_challenges, challenges_subtasks,
+    challenges_user_subtasks,
+    sea_orm_active_enums::{ChallengesBanAction, ChallengesJudgePriority, ChallengesVerdict},
 };
 use fnct::{format::JsonFormatter, key};
-use key_rwlock::KeyRwLock;
+use futures::{
+    channel::mpsc,
+    stream::{self, BoxStream},
+    Stream, StreamExt,
+};
 use lib::{
     auth::{AdminAuth, VerifiedUserAuth},
     config::Config,
     Cache, SharedState,
 };
 use poem::web::Data;
-use poem_ext::{db::DbTxn, response, responses::ErrorResponse};
-use poem_openapi::{param::Path, payload::Json, OpenApi};
-use sandkasten_client::{schemas::environments::Environment, SandkastenClient};
-use schemas::challenges::coding_challenges::{QueueStatus, Submission, SubmissionContent};
+use poem_ext::{db::DbTxn, response, responses::ErrorResponse, static_string};
+use poem_openapi::{
+    param::{Path, Query},
+    payload::{EventStream, Json},
+    ApiResponse, Object, OpenApi,
+};
+use sandkasten_client::{
+    schemas::{
+        environments::Environment,
+        programs::{File, ResourceUsage, RunResult},
+    },
+    SandkastenClient,
+};
+use schemas::challenges::coding_challenges::{
+    CheckResult, QueueStatus, RejudgeRequest, RejudgeStatus, ReplayRequest, ReplayResult,
+    ShareSubmissionRequest, SharedSolution, SharedSolutionOrder, Submission, SubmissionContent,
+    SubmissionEvent, SubmissionProgress, SubmissionQueuePosition, TestResult,
+};
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, DatabaseTransaction, DbErr, EntityTrait,
-    ModelTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
+    sea_query::{self, Alias, Expr, OnConflict, SelectStatement, SimpleExpr},
+    ActiveEnum, ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr,
+    EntityTrait, ModelTrait, QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait,
+    Unchanged,
 };
 use thiserror::Error;
-use tokio::sync::{RwLock, Semaphore};
 use tracing::{debug, error, trace};
 use uuid::Uuid;
 
-use super::{check_challenge, CheckChallenge, CheckError, CheckTestcaseError};
+use super::{run_testcases, score, test_data::get_test_data, CheckChallenge, CheckError, Testcase};
 use crate::{
     endpoints::Tags,
     services::{
-        judge::{self, Judge},
+        judge::{self, test_data_seed, Checker, Judge},
+        judge_queue,
+        metrics::{JUDGE_DURATION, JUDGE_VERDICTS},
+        submission_events::{Message, SubmissionEvents},
         subtasks::{
-            deduct_hearts, get_subtask, get_user_subtask, send_task_rewards, update_user_subtask,
-            SendTaskRewardsError, UserSubtaskExt,
+            get_active_ban, get_subtask, get_user_subtask, lock_user_subtask, pay_hearts,
+            query_subtask_admin, send_partial_task_rewards, update_user_subtask, ActiveBan,
+            QuerySubtaskAdminError, SendTaskRewardsError, UserSubtaskExt,
         },
     },
 };
@@ -45,22 +75,19 @@ pub struct Api {
     pub config: Arc<Config>,
     pub sandkasten: SandkastenClient,
     pub judge_cache: Cache<JsonFormatter>,
-    pub judge_lock: Arc<Semaphore>,
-    pub reward_lock: Arc<KeyRwLock<(Uuid, Uuid)>>,
-    pub queue_positions: Arc<RwLock<QueuePositions>>,
+    pub submission_events: SubmissionEvents,
 }
 
 #[OpenApi(tag = "Tags::CodingChallenges")]
 impl Api {
     /// Return the current judge queue status.
     #[oai(path = "/coding_challenges/queue", method = "get")]
-    async fn get_queue_status(&self, _auth: AdminAuth) -> GetQueueStatus::Response<AdminAuth> {
-        let qp = self.queue_positions.read().await;
-        GetQueueStatus::ok(QueueStatus {
-            workers: qp.workers(),
-            active: qp.active(),
-            waiting: qp.waiting(),
-        })
+    async fn get_queue_status(
+        &self,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> GetQueueStatus::Response<AdminAuth> {
+        GetQueueStatus::ok(judge_queue::status(&***db, lease_duration(&self.config)).await?)
     }
 
     /// List all submissions of a coding challenge.
@@ -85,18 +112,24 @@ impl Api {
             return ListSubmissions::subtask_not_found();
         }
 
-        let queue_positions = self.queue_positions.read().await;
+        let submissions = cc
+            .find_related(challenges_coding_challenge_submissions::Entity)
+            .filter(challenges_coding_challenge_submissions::Column::Creator.eq(auth.0.id))
+            .order_by_desc(challenges_coding_challenge_submissions::Column::CreationTimestamp)
+            .all(&***db)
+            .await?;
+        let mut results =
+            get_latest_results(&***db, submissions.iter().map(|submission| submission.id)).await?;
+        let queue_positions =
+            judge_queue::positions(&***db, submissions.iter().map(|submission| submission.id))
+                .await?;
         ListSubmissions::ok(
-            cc.find_related(challenges_coding_challenge_submissions::Entity)
-                .filter(challenges_coding_challenge_submissions::Column::Creator.eq(auth.0.id))
-                .find_also_related(challenges_coding_challenge_result::Entity)
-                .order_by_desc(challenges_coding_challenge_submissions::Column::CreationTimestamp)
-                .all(&***db)
-                .await?
+            submissions
                 .into_iter()
-                .map(|(submission, result)| {
-                    let position = queue_positions.position(submission.id);
-                    Submission::from(&submission, result.map(Into::into), position)
+                .map(|submission| {
+                    let result = results.remove(&submission.id);
+                    let position = queue_positions.get(&submission.id).copied();
+                    Submission::from(&submission, result, position)
                 })
                 .collect(),
         )
@@ -137,315 +170,1411 @@ impl Api {
             return GetSubmission::submission_not_found();
         };
 
+        let files = get_submission_files(&***db, submission.id).await?;
         GetSubmission::ok(SubmissionContent {
             environment: submission.environment,
             code: submission.code,
+            files,
         })
     }
 
-    /// Create a submission for a coding challenge.
+    /// Stream updates on the status of a submission as server-sent events.
+    ///
+    /// The first event contains the current queue position of the submission,
+    /// or its result if it has already been judged. The stream is closed after
+    /// the result has been sent.
     #[oai(
-        path = "/tasks/:task_id/coding_challenges/:subtask_id/submissions",
-        method = "post"
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/submissions/:submission_id/events",
+        method = "get"
     )]
-    async fn create_submission(
+    async fn get_submission_events(
         &self,
         task_id: Path<Uuid>,
         subtask_id: Path<Uuid>,
-        data: Json<SubmissionContent>,
+        submission_id: Path<Uuid>,
         db: Data<&DbTxn>,
         auth: VerifiedUserAuth,
-    ) -> CreateSubmission::Response<VerifiedUserAuth> {
+    ) -> poem_ext::responses::Response<GetSubmissionEvents, VerifiedUserAuth> {
         let Some((cc, subtask)) =
             get_subtask::<challenges_coding_challenges::Entity>(&db, task_id.0, subtask_id.0)
                 .await?
         else {
-            return CreateSubmission::subtask_not_found();
+            return GetSubmissionEvents::submission_not_found();
         };
         if !auth.0.admin && auth.0.id != subtask.creator && !subtask.enabled {
-            return CreateSubmission::subtask_not_found();
+            return GetSubmissionEvents::submission_not_found();
         }
 
-        if !self
-            .get_environments()
-            .await?
-            .contains_key(&data.0.environment)
-        {
-            return CreateSubmission::environment_not_found();
-        }
+        let Some(submission) =
+            challenges_coding_challenge_submissions::Entity::find_by_id(submission_id.0)
+                .filter(
+                    challenges_coding_challenge_submissions::Column::SubtaskId.eq(cc.subtask_id),
+                )
+                .filter(challenges_coding_challenge_submissions::Column::Creator.eq(auth.0.id))
+                .one(&***db)
+                .await?
+        else {
+            return GetSubmissionEvents::submission_not_found();
+        };
 
-        let user_subtask = get_user_subtask(&db, auth.0.id, subtask.id).await?;
+        // subscribe before loading the current state to not miss any updates
+        let messages = self.submission_events.subscribe(submission.id).await?;
+        let stream =
+            submission_event_stream(self.state.db.clone(), submission.id, messages).await?;
+        Ok(
+            GetSubmissionEvents::Ok(EventStream::new(stream).keep_alive(Duration::from_secs(15)))
+                .into(),
+        )
+    }
 
-        if let Some(last_attempt) = user_subtask.last_attempt() {
-            let time_left = self.config.challenges.coding_challenges.timeout as i64
-                - (Utc::now() - last_attempt).num_seconds();
-            if time_left > 0 {
-                return CreateSubmission::too_many_requests(time_left as u64);
-            }
+    /// List the results of all tests that have been run for the latest result
+    /// of a submission.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/submissions/:submission_id/tests",
+        method = "get"
+    )]
+    async fn list_submission_tests(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        submission_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ListSubmissionTests::Response<VerifiedUserAuth> {
+        let Some((cc, subtask)) =
+            get_subtask::<challenges_coding_challenges::Entity>(&db, task_id.0, subtask_id.0)
+                .await?
+        else {
+            return ListSubmissionTests::submission_not_found();
+        };
+        if !auth.0.admin && auth.0.id != subtask.creator && !subtask.enabled {
+            return ListSubmissionTests::submission_not_found();
         }
 
-        if !deduct_hearts(&self.state.services, &self.config, &auth.0, &subtask).await? {
-            return CreateSubmission::not_enough_hearts();
-        }
+        let Some(submission) =
+            challenges_coding_challenge_submissions::Entity::find_by_id(submission_id.0)
+                .filter(
+                    challenges_coding_challenge_submissions::Column::SubtaskId.eq(cc.subtask_id),
+                )
+                .filter(challenges_coding_challenge_submissions::Column::Creator.eq(auth.0.id))
+                .one(&***db)
+                .await?
+        else {
+            return ListSubmissionTests::submission_not_found();
+        };
 
-        let submission = Arc::new(
-            challenges_coding_challenge_submissions::ActiveModel {
-                id: Set(Uuid::new_v4()),
-                subtask_id: Set(cc.subtask_id),
-                creator: Set(auth.0.id),
-                creation_timestamp: Set(Utc::now().naive_utc()),
-                environment: Set(data.0.environment),
-                code: Set(data.0.code),
+        let Some(result) = get_latest_results(&***db, [submission.id])
+            .await?
+            .remove(&submission.id)
+        else {
+            return ListSubmissionTests::ok(Vec::new());
+        };
+
+        ListSubmissionTests::ok(
+            result
+                .find_related(challenges_coding_challenge_test_result::Entity)
+                .order_by_asc(challenges_coding_challenge_test_result::Column::Index)
+                .all(&***db)
+                .await?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        )
+    }
+
+    /// Run a submission again on a single test case, by default on the first
+    /// test case it failed on, and return the full output.
+    ///
+    /// Only admins and the creator of the coding challenge may replay
+    /// submissions.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/submissions/:submission_id/replay",
+        method = "post"
+    )]
+    async fn replay_submission(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        submission_id: Path<Uuid>,
+        data: Json<ReplayRequest>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ReplaySubmission::Response<VerifiedUserAuth> {
+        let cc = match query_subtask_admin::<challenges_coding_challenges::Entity, _>(
+            &db,
+            &auth.0,
+            task_id.0,
+            subtask_id.0,
+            |cc, _| cc,
+        )
+        .await?
+        {
+            Ok(cc) => cc,
+            Err(QuerySubtaskAdminError::NotFound) => {
+                return ReplaySubmission::submission_not_found()
             }
-            .insert(&***db)
-            .await?,
+            Err(QuerySubtaskAdminError::NoAccess) => return ReplaySubmission::forbidden(),
+        };
+
+        let Some(submission) =
+            challenges_coding_challenge_submissions::Entity::find_by_id(submission_id.0)
+                .filter(
+                    challenges_coding_challenge_submissions::Column::SubtaskId.eq(cc.subtask_id),
+                )
+                .one(&***db)
+                .await?
+        else {
+            return ReplaySubmission::submission_not_found();
+        };
+
+        let seed = match data.0.seed {
+            Some(seed) => seed,
+            None => match get_latest_results(&***db, [submission.id])
+                .await?
+                .remove(&submission.id)
+                .and_then(|result| result.failed_seed)
+            {
+                Some(seed) => seed,
+                None => return ReplaySubmission::no_failed_test(),
+            },
+        };
+
+        let files = get_submission_files(&***db, submission.id).await?;
+        let test_data = get_test_data(&***db, cc.subtask_id).await?;
+        let judge = Judge::new(
+            &self.sandkasten,
+            &self.judge_cache,
+            &self.config.challenges.coding_challenges,
+            &cc,
         );
+        let result = match test_data
+            .iter()
+            .find(|test| test_data_seed(test.id) == seed)
+        {
+            Some(test) => judge
+                .run_test_data(
+                    test,
+                    &submission.environment,
+                    &submission.code,
+                    &files,
+                    Some(cc.time_limit as _),
+                    Some(cc.memory_limit as _),
+                )
+                .await
+                .map(|result| (test.input.clone(), result)),
+            None => {
+                async {
+                    let input = judge.generate(&seed).await?;
+                    let result = judge
+                        .run_test(
+                            &seed,
+                            &submission.environment,
+                            &submission.code,
+                            &files,
+                            Some(cc.time_limit as _),
+                            Some(cc.memory_limit as _),
+                        )
+                        .await?;
+                    Ok((input.input, result))
+                }
+                .await
+            }
+        };
+        let (input, result) = match result {
+            Err(judge::Error::EvaluatorFailed(err) | judge::Error::InvalidOutput(err)) => {
+                error!(
+                    "evaluator for {} failed to execute while replaying {} on {}: {:?}",
+                    cc.subtask_id, submission.id, seed, err
+                );
+                return ReplaySubmission::evaluator_failed();
+            }
+            Err(judge::Error::EnvironmentNotFound) => {
+                return ReplaySubmission::environment_not_found();
+            }
+            x => x?,
+        };
 
-        let position = start_judge_submission_task(StartJudgeSubmissionTask {
-            submission: Arc::clone(&submission),
-            subtask,
-            judge_lock: Arc::clone(&self.judge_lock),
-            db: self.state.db.clone(),
-            sandkasten: self.sandkasten.clone(),
-            cache: self.judge_cache.clone(),
-            reward_lock: Arc::clone(&self.reward_lock),
-            state: Arc::clone(&self.state),
-            challenge: Arc::new(cc),
-            user_subtask,
-            queue_positions: Arc::clone(&self.queue_positions),
+        ReplaySubmission::ok(ReplayResult {
+            seed,
+            input,
+            result,
         })
-        .await;
-
-        CreateSubmission::ok(Submission::from(&submission, None, Some(position)))
     }
-}
-
-response!(GetQueueStatus = {
-    Ok(200) => QueueStatus,
-});
 
-response!(ListSubmissions = {
-    Ok(200) => Vec<Submission>,
-    /// Subtask does not exist.
-    SubtaskNotFound(404, error),
-});
+    /// Share an accepted submission with the users who have solved the coding
+    /// challenge, or stop sharing it.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/submissions/:submission_id/share",
+        method = "put"
+    )]
+    async fn share_submission(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        submission_id: Path<Uuid>,
+        data: Json<ShareSubmissionRequest>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ShareSubmission::Response<VerifiedUserAuth> {
+        let Some((cc, subtask)) =
+            get_subtask::<challenges_coding_challenges::Entity>(&db, task_id.0, subtask_id.0)
+                .await?
+        else {
+            return ShareSubmission::submission_not_found();
+        };
+        if !auth.0.admin && auth.0.id != subtask.creator && !subtask.enabled {
+            return ShareSubmission::submission_not_found();
+        }
 
-response!(GetSubmission = {
-    Ok(200) => SubmissionContent,
-    /// Submission does not exist.
-    SubmissionNotFound(404, error),
-});
+        let Some(submission) =
+            challenges_coding_challenge_submissions::Entity::find_by_id(submission_id.0)
+                .filter(
+                    challenges_coding_challenge_submissions::Column::SubtaskId.eq(cc.subtask_id),
+                )
+                .filter(challenges_coding_challenge_submissions::Column::Creator.eq(auth.0.id))
+                .one(&***db)
+                .await?
+        else {
+            return ShareSubmission::submission_not_found();
+        };
 
-response!(CreateSubmission = {
-    Ok(201) => Submission,
-    /// Try again later. `details` contains the number of seconds to wait.
-    TooManyRequests(429, error) => u64,
-    /// Subtask does not exist.
-    SubtaskNotFound(404, error),
-    /// The solution environment does not exist.
-    EnvironmentNotFound(404, error),
-    /// The user does not have enough hearts to submit a solution and is neither an admin nor the creator of this subtask.
-    NotEnoughHearts(403, error),
-});
+        let result = get_latest_results(&***db, [submission.id])
+            .await?
+            .remove(&submission.id);
+        if data.0.shared
+            && result
+                .as_ref()
+                .is_none_or(|result| result.verdict != ChallengesVerdict::Ok)
+        {
+            return ShareSubmission::not_accepted();
+        }
 
-struct StartJudgeSubmissionTask {
-    submission: Arc<challenges_coding_challenge_submissions::Model>,
-    subtask: challenges_subtasks::Model,
-    judge_lock: Arc<Semaphore>,
-    db: DatabaseConnection,
-    sandkasten: SandkastenClient,
-    cache: Cache<JsonFormatter>,
-    reward_lock: Arc<KeyRwLock<(Uuid, Uuid)>>,
-    state: Arc<SharedState>,
-    challenge: Arc<challenges_coding_challenges::Model>,
-    user_subtask: Option<challenges_user_subtasks::Model>,
-    queue_positions: Arc<RwLock<QueuePositions>>,
-}
+        let submission = challenges_coding_challenge_submissions::ActiveModel {
+            id: Unchanged(submission.id),
+            shared: Set(data.0.shared),
+            ..Default::default()
+        }
+        .update(&***db)
+        .await?;
+        let position = judge_queue::positions(&***db, [submission.id])
+            .await?
+            .get(&submission.id)
+            .copied();
+        ShareSubmission::ok(Submission::from(&submission, result, position))
+    }
 
-async fn start_judge_submission_task(
-    StartJudgeSubmissionTask {
-        submission,
-        judge_lock,
-        db,
-        sandkasten,
-        cache,
-        reward_lock,
-        state,
-        challenge: cc,
-        queue_positions,
-        subtask,
-        user_subtask,
-    }: StartJudgeSubmissionTask,
-) -> usize {
-    let position = queue_positions.write().await.push(submission.id);
-    trace!(
-        "submission {} enqueued at position {}",
-        submission.id,
-        position
-    );
-    tokio::spawn({
-        async move {
-            let submission_id = submission.id;
-            let pop = || async {
-                if !queue_positions.write().await.pop(submission_id) {
-                    error!("judge task for {submission_id} failed to pop queue position");
-                }
-            };
-            let Ok(_guard) = judge_lock.acquire().await else {
-                error!("judge task for {submission_id} failed to acquire lock",);
-                // don't pop here since we didn't get the semaphore permit
-                return;
-            };
-            let db = match db.begin().await {
-                Ok(x) => x,
-                Err(err) => {
-                    error!("judge task for {submission_id} failed to start db transaction: {err}",);
-                    pop().await;
-                    return;
-                }
-            };
-            let judge = Judge {
-                sandkasten: &sandkasten,
-                evaluator: &cc.evaluator,
-                cache: &cache,
-            };
-            if let Err(err) = judge_submission(JudgeSubmission {
-                db: &db,
-                subtask: &subtask,
-                challenge: &cc,
-                submission,
-                judge,
-                reward_lock,
-                state,
-                user_subtask,
-            })
-            .await
+    /// List the shared accepted submissions of a coding challenge.
+    ///
+    /// Only users who have solved the coding challenge can see the solutions
+    /// of other users.
+    #[allow(clippy::too_many_arguments)]
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/solutions",
+        method = "get"
+    )]
+    async fn list_shared_solutions(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        /// Only return solutions written for this environment.
+        environment: Query<Option<String>>,
+        /// The order in which solutions are returned.
+        order: Query<Option<SharedSolutionOrder>>,
+        /// Maximum number of solutions to return
+        limit: Query<Option<u64>>,
+        /// Pagination offset
+        offset: Query<Option<u64>>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ListSharedSolutions::Response<VerifiedUserAuth> {
+        let Some((cc, subtask)) =
+            get_subtask::<challenges_coding_challenges::Entity>(&db, task_id.0, subtask_id.0)
+                .await?
+        else {
+            return ListSharedSolutions::subtask_not_found();
+        };
+        if !auth.0.admin && auth.0.id != subtask.creator {
+            if !subtask.enabled {
+                return ListSharedSolutions::subtask_not_found();
+            }
+            if !get_user_subtask(&db, auth.0.id, subtask.id)
+                .await?
+                .is_solved()
             {
-                error!("judge task for {submission_id} failed: {err}");
-                db.rollback().await.ok();
-            } else if let Err(err) = db.commit().await {
-                error!("judge task for {submission_id} failed to commit db transaction: {err}");
+                return ListSharedSolutions::not_solved();
             }
-            pop().await;
         }
-    });
 
-    position
-}
+        let mut query = challenges_coding_challenge_submissions::Entity::find()
+            .find_also_related(challenges_coding_challenge_result::Entity)
+            .filter(challenges_coding_challenge_submissions::Column::SubtaskId.eq(cc.subtask_id))
+            .filter(challenges_coding_challenge_submissions::Column::Shared.eq(true))
+            .filter(challenges_coding_challenge_result::Column::Verdict.eq(ChallengesVerdict::Ok))
+            // only the latest result of each submission
+            .filter(Expr::exists(get_newer_result_query()).not());
+        if let Some(environment) = environment.0 {
+            query = query.filter(
+                challenges_coding_challenge_submissions::Column::Environment.eq(environment),
+            );
+        }
+        query = match order.0.unwrap_or(SharedSolutionOrder::CreationTimestamp) {
+            SharedSolutionOrder::CreationTimestamp => query,
+            SharedSolutionOrder::RunTime => query.order_by_asc(get_max_test_result_query(
+                challenges_coding_challenge_test_result::Column::RunTime,
+            )),
+            SharedSolutionOrder::RunMemory => query.order_by_asc(get_max_test_result_query(
+                challenges_coding_challenge_test_result::Column::RunMemory,
+            )),
+        };
+        let solutions = query
+            .order_by_desc(challenges_coding_challenge_submissions::Column::CreationTimestamp)
+            .limit(limit.0)
+            .offset(offset.0)
+            .all(&***db)
+            .await?;
 
-struct JudgeSubmission<'a, 'b> {
-    db: &'a DatabaseTransaction,
-    subtask: &'a challenges_subtasks::Model,
-    challenge: &'a challenges_coding_challenges::Model,
-    submission: Arc<challenges_coding_challenge_submissions::Model>,
-    judge: Judge<'b>,
-    reward_lock: Arc<KeyRwLock<(Uuid, Uuid)>>,
-    state: Arc<SharedState>,
-    user_subtask: Option<challenges_user_subtasks::Model>,
-}
+        let resource_usage = get_max_resource_usage(
+            &***db,
+            solutions
+                .iter()
+                .filter_map(|(_, result)| Some(result.as_ref()?.id)),
+        )
+        .await?;
+        let mut files = get_files_of_submissions(
+            &***db,
+            solutions.iter().map(|(submission, _)| submission.id),
+        )
+        .await?;
+        ListSharedSolutions::ok(
+            solutions
+                .into_iter()
+                .map(|(submission, result)| {
+                    let (time, memory) = result
+                        .and_then(|result| resource_usage.get(&result.id).copied())
+                        .unwrap_or_default();
+                    SharedSolution {
+                        id: submission.id,
+                        creator: submission.creator,
+                        creation_timestamp: submission.creation_timestamp.and_utc(),
+                        files: files.remove(&submission.id).unwrap_or_default(),
+                        environment: submission.environment,
+                        code: submission.code,
+                        resource_usage: time.zip(memory).map(|(time, memory)| ResourceUsage {
+                            time: time as _,
+                            memory: memory as _,
+                        }),
+                    }
+                })
+                .collect(),
+        )
+    }
 
-async fn judge_submission(
-    JudgeSubmission {
-        db,
-        subtask,
-        challenge,
-        submission,
-        judge,
-        reward_lock,
-        state,
-        user_subtask,
-    }: JudgeSubmission<'_, '_>,
-) -> Result<(), JudgeSubmissionError> {
-    debug!("judging submission {}", submission.id);
-    let result = check_challenge(CheckChallenge {
-        judge,
-        challenge_id: challenge.subtask_id,
-        solution_environment: &submission.environment,
-        solution_code: &submission.code,
-        time_limit: challenge.time_limit as _,
-        memory_limit: challenge.memory_limit as _,
-        static_tests: challenge.static_tests as _,
-        random_tests: challenge.random_tests as _,
-    })
-    .await?;
-    trace!("judge result for {}: {result:?}", submission.id);
-    match result {
-        Ok(()) => {
-            let _guard = reward_lock
-                .write((submission.subtask_id, submission.creator))
-                .await;
+    /// List the submissions of all users for a coding challenge, starting
+    /// with the newest ones.
+    ///
+    /// Only admins and the creator of the coding challenge may list the
+    /// submissions of other users.
+    #[allow(clippy::too_many_arguments)]
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/all_submissions",
+        method = "get"
+    )]
+    async fn list_all_submissions(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        /// Only return submissions whose latest result has this verdict.
+        verdict: Query<Option<ChallengesVerdict>>,
+        /// Only return submissions written for this environment.
+        environment: Query<Option<String>>,
+        /// Only return submissions of this user.
+        creator: Query<Option<Uuid>>,
+        /// Maximum number of submissions to return
+        limit: Query<Option<u64>>,
+        /// Pagination offset
+        offset: Query<Option<u64>>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ListAllSubmissions::Response<VerifiedUserAuth> {
+        let cc = match query_subtask_admin::<challenges_coding_challenges::Entity, _>(
+            &db,
+            &auth.0,
+            task_id.0,
+            subtask_id.0,
+            |cc, _| cc,
+        )
+        .await?
+        {
+            Ok(cc) => cc,
+            Err(QuerySubtaskAdminError::NotFound) => {
+                return ListAllSubmissions::subtask_not_found()
+            }
+            Err(QuerySubtaskAdminError::NoAccess) => return ListAllSubmissions::forbidden(),
+        };
 
-            let solved_previously = user_subtask.is_solved();
-            if !solved_previously {
-                update_user_subtask(
-                    db,
-                    user_subtask.as_ref(),
-                    challenges_user_subtasks::ActiveModel {
-                        user_id: Set(submission.creator),
-                        subtask_id: Set(subtask.id),
-                        solved_timestamp: Set(Some(submission.creation_timestamp)),
-                        last_attempt_timestamp: Set(Some(submission.creation_timestamp)),
-                        attempts: Set(user_subtask.attempts() as i32 + 1),
-                        ..Default::default()
-                    },
+        let mut query = challenges_coding_challenge_submissions::Entity::find()
+            .find_also_related(challenges_coding_challenge_result::Entity)
+            .filter(challenges_coding_challenge_submissions::Column::SubtaskId.eq(cc.subtask_id))
+            // only the latest result of each submission
+            .filter(Expr::exists(get_newer_result_query()).not());
+        if let Some(verdict) = verdict.0 {
+            query = query.filter(challenges_coding_challenge_result::Column::Verdict.eq(verdict));
+        }
+        if let Some(environment) = environment.0 {
+            query = query.filter(
+                challenges_coding_challenge_submissions::Column::Environment.eq(environment),
+            );
+        }
+        if let Some(creator) = creator.0 {
+            query =
+                query.filter(challenges_coding_challenge_submissions::Column::Creator.eq(creator));
+        }
+        let submissions = query
+            .order_by_desc(challenges_coding_challenge_submissions::Column::CreationTimestamp)
+            .limit(limit.0)
+            .offset(offset.0)
+            .all(&***db)
+            .await?;
+        let queue_positions = judge_queue::positions(
+            &***db,
+            submissions.iter().map(|(submission, _)| submission.id),
+        )
+        .await?;
+        ListAllSubmissions::ok(
+            submissions
+                .into_iter()
+                .map(|(submission, result)| {
+                    let position = queue_positions.get(&submission.id).copied();
+                    Submission::from(&submission, result, position)
+                })
+                .collect(),
+        )
+    }
+
+    /// Return the code of any submission of a coding challenge.
+    ///
+    /// Only admins and the creator of the coding challenge may view the
+    /// submissions of other users.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/all_submissions/:submission_id",
+        method = "get"
+    )]
+    async fn get_any_submission(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        submission_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetAnySubmission::Response<VerifiedUserAuth> {
+        let cc = match query_subtask_admin::<challenges_coding_challenges::Entity, _>(
+            &db,
+            &auth.0,
+            task_id.0,
+            subtask_id.0,
+            |cc, _| cc,
+        )
+        .await?
+        {
+            Ok(cc) => cc,
+            Err(QuerySubtaskAdminError::NotFound) => {
+                return GetAnySubmission::submission_not_found()
+            }
+            Err(QuerySubtaskAdminError::NoAccess) => return GetAnySubmission::forbidden(),
+        };
+
+        let Some(submission) =
+            challenges_coding_challenge_submissions::Entity::find_by_id(submission_id.0)
+                .filter(
+                    challenges_coding_challenge_submissions::Column::SubtaskId.eq(cc.subtask_id),
                 )
-                .await?;
+                .one(&***db)
+                .await?
+        else {
+            return GetAnySubmission::submission_not_found();
+        };
+
+        let files = get_submission_files(&***db, submission.id).await?;
+        GetAnySubmission::ok(SubmissionContent {
+            environment: submission.environment,
+            code: submission.code,
+            files,
+        })
+    }
+
+    /// Return the rejudge status of a coding challenge.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/rejudge",
+        method = "get"
+    )]
+    async fn get_rejudge_status(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> GetRejudgeStatus::Response<AdminAuth> {
+        let Some((cc, subtask)) =
+            get_subtask::<challenges_coding_challenges::Entity>(&db, task_id.0, subtask_id.0)
+                .await?
+        else {
+            return GetRejudgeStatus::subtask_not_found();
+        };
+
+        GetRejudgeStatus::ok(get_rejudge_status(&***db, &cc, &subtask).await?)
+    }
+
+    /// Judge the submissions of a coding challenge again, e.g. after the
+    /// evaluator or the limits have been changed.
+    ///
+    /// Users whose submissions are accepted now receive the rewards for the
+    /// coding challenge. Users who have already solved it keep their rewards,
+    /// even if none of their submissions pass anymore. These users are
+    /// reported in the `no_longer_passing` field of the rejudge status.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/rejudge",
+        method = "post"
+    )]
+    async fn rejudge(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        data: Json<RejudgeRequest>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> Rejudge::Response<AdminAuth> {
+        let Some((cc, subtask)) =
+            get_subtask::<challenges_coding_challenges::Entity>(&db, task_id.0, subtask_id.0)
+                .await?
+        else {
+            return Rejudge::subtask_not_found();
+        };
+
+        let mut query = cc.find_related(challenges_coding_challenge_submissions::Entity);
+        if let Some(since) = data.0.since {
+            query = query.filter(
+                challenges_coding_challenge_submissions::Column::CreationTimestamp
+                    .gte(since.naive_utc()),
+            );
+        }
+        if let Some(until) = data.0.until {
+            query = query.filter(
+                challenges_coding_challenge_submissions::Column::CreationTimestamp
+                    .lt(until.naive_utc()),
+            );
+        }
+        let mut submissions = query.all(&***db).await?;
+        if let Some(verdicts) = &data.0.verdicts {
+            let results =
+                get_latest_results(&***db, submissions.iter().map(|submission| submission.id))
+                    .await?;
+            submissions.retain(|submission| {
+                results
+                    .get(&submission.id)
+                    .is_some_and(|result| verdicts.contains(&result.verdict))
+            });
+        }
+
+        let enqueued = judge_queue::enqueue_rejudge(&***db, &submissions).await?;
+        debug!(
+            "enqueued {enqueued} submission(s) of {} for rejudge",
+            cc.subtask_id
+        );
+
+        Rejudge::ok(get_rejudge_status(&***db, &cc, &subtask).await?)
+    }
+
+    /// Return all submissions that could not be judged because of an internal
+    /// error and are not currently waiting to be judged again.
+    #[oai(
+        path = "/coding_challenges/submissions/internal_errors",
+        method = "get"
+    )]
+    async fn list_internal_errors(
+        &self,
+        /// Maximum number of submissions to return
+        limit: Query<Option<u64>>,
+        /// Pagination offset
+        offset: Query<Option<u64>>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> ListInternalErrors::Response<AdminAuth> {
+        let queued = sea_query::Query::select()
+            .column(challenges_coding_challenge_judge_jobs::Column::SubmissionId)
+            .from(challenges_coding_challenge_judge_jobs::Entity)
+            .to_owned();
+
+        ListInternalErrors::ok(
+            challenges_coding_challenge_submissions::Entity::find()
+                .find_also_related(challenges_coding_challenge_result::Entity)
+                .filter(
+                    challenges_coding_challenge_result::Column::Verdict
+                        .eq(ChallengesVerdict::InternalError),
+                )
+                // only the latest result of each submission
+                .filter(Expr::exists(get_newer_result_query()).not())
+                .filter(challenges_coding_challenge_submissions::Column::Id.not_in_subquery(queued))
+                .order_by_desc(challenges_coding_challenge_submissions::Column::CreationTimestamp)
+                .limit(limit.0)
+                .offset(offset.0)
+                .all(&***db)
+                .await?
+                .into_iter()
+                .map(|(submission, result)| Submission::from(&submission, result, None))
+                .collect(),
+        )
+    }
+
+    /// Judge a submission again that could not be judged because of an
+    /// internal error.
+    #[oai(
+        path = "/coding_challenges/submissions/:submission_id/retry",
+        method = "post"
+    )]
+    async fn retry_submission(
+        &self,
+        submission_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        _auth: AdminAuth,
+    ) -> RetrySubmission::Response<AdminAuth> {
+        let Some(submission) =
+            challenges_coding_challenge_submissions::Entity::find_by_id(submission_id.0)
+                .one(&***db)
+                .await?
+        else {
+            return RetrySubmission::submission_not_found();
+        };
+        if judge_queue::position(&***db, submission.id)
+            .await?
+            .is_some()
+        {
+            return RetrySubmission::already_queued();
+        }
+        if !get_latest_results(&***db, [submission.id])
+            .await?
+            .remove(&submission.id)
+            .is_some_and(|result| result.verdict == ChallengesVerdict::InternalError)
+        {
+            return RetrySubmission::no_internal_error();
+        }
+
+        judge_queue::enqueue(&***db, &submission, ChallengesJudgePriority::High).await?;
+        let position = judge_queue::position(&***db, submission.id).await?;
+        RetrySubmission::ok(Submission::from(&submission, None, position))
+    }
+
+    /// Create a submission for a coding challenge.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/submissions",
+        method = "post"
+    )]
+    async fn create_submission(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        data: Json<SubmissionContent>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> CreateSubmission::Response<VerifiedUserAuth> {
+        let Some((cc, subtask)) =
+            get_subtask::<challenges_coding_challenges::Entity>(&db, task_id.0, subtask_id.0)
+                .await?
+        else {
+            return CreateSubmission::subtask_not_found();
+        };
+        if !auth.0.admin && auth.0.id != subtask.creator && !subtask.enabled {
+            return CreateSubmission::subtask_not_found();
+        }
+
+        if !self
+            .get_environments()
+            .await?
+            .contains_key(&data.0.environment)
+        {
+            return CreateSubmission::environment_not_found();
+        }
+
+        match get_active_ban(&db, &auth.0, ChallengesBanAction::Submit).await? {
+            ActiveBan::NotBanned => {}
+            ActiveBan::Temporary(end) => return CreateSubmission::banned(Some(end)),
+            ActiveBan::Permanent => return CreateSubmission::banned(None),
+        }
+
+        let mut file_names = HashSet::new();
+        if !data
+            .0
+            .files
+            .iter()
+            .all(|file| file_names.insert(&file.name))
+        {
+            return CreateSubmission::duplicate_file_name();
+        }
+
+        // identical resubmissions would receive the same verdict, unless the
+        // previous verdict was caused by a timeout or an internal error
+        let test_data = get_test_data(&***db, cc.subtask_id).await?;
+        let hash = hash_submission(
+            &Judge::new(
+                &self.sandkasten,
+                &self.judge_cache,
+                &self.config.challenges.coding_challenges,
+                &cc,
+            ),
+            &cc,
+            &test_data,
+            &data.0.environment,
+            &data.0.code,
+            &data.0.files,
+        )
+        .await?;
+        let duplicates = challenges_coding_challenge_submissions::Entity::find()
+            .filter(challenges_coding_challenge_submissions::Column::SubtaskId.eq(cc.subtask_id))
+            .filter(challenges_coding_challenge_submissions::Column::Creator.eq(auth.0.id))
+            .filter(challenges_coding_challenge_submissions::Column::Hash.eq(&hash))
+            .all(&***db)
+            .await?;
+        let results = get_latest_results(&***db, duplicates.iter().map(|x| x.id)).await?;
+        if let Some(submission) = duplicates.iter().find(|submission| {
+            results.get(&submission.id).is_none_or(|result| {
+                !matches!(
+                    result.verdict,
+                    ChallengesVerdict::InternalError | ChallengesVerdict::TimeLimitExceeded
+                )
+            })
+        }) {
+            return CreateSubmission::duplicate_submission(submission.id);
+        }
+
+        let user_subtask = get_user_subtask(&db, auth.0.id, subtask.id).await?;
+
+        if let Some(last_attempt) = user_subtask.last_attempt() {
+            let time_left = self.config.challenges.coding_challenges.timeout as i64
+                - (Utc::now() - last_attempt).num_seconds();
+            if time_left > 0 {
+                return CreateSubmission::too_many_requests(time_left as u64);
+            }
+        }
+
+        let config = &self.config.challenges.coding_challenges;
+        if judge_queue::pending(&***db, auth.0.id).await? >= config.max_pending_submissions {
+            return CreateSubmission::too_many_pending_submissions();
+        }
+
+        let Some(hearts) =
+            pay_hearts(&self.state.services, &self.config, &auth.0, &subtask).await?
+        else {
+            return CreateSubmission::not_enough_hearts();
+        };
+
+        let submission = challenges_coding_challenge_submissions::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            subtask_id: Set(cc.subtask_id),
+            creator: Set(auth.0.id),
+            creation_timestamp: Set(Utc::now().naive_utc()),
+            environment: Set(data.0.environment),
+            code: Set(data.0.code),
+            hash: Set(Some(hash)),
+            hearts: Set(hearts as _),
+            shared: Set(false),
+        }
+        .insert(&***db)
+        .await?;
+        if !data.0.files.is_empty() {
+            challenges_coding_challenge_submission_files::Entity::insert_many(
+                data.0.files.into_iter().map(|file| {
+                    challenges_coding_challenge_submission_files::ActiveModel {
+                        submission_id: Set(submission.id),
+                        name: Set(file.name),
+                        content: Set(file.content),
+                    }
+                }),
+            )
+            .exec(&***db)
+            .await?;
+        }
+        let priority = if auth.0.admin || auth.0.id == subtask.creator {
+            ChallengesJudgePriority::High
+        } else {
+            ChallengesJudgePriority::Normal
+        };
+        judge_queue::enqueue(&***db, &submission, priority).await?;
+        let position = judge_queue::position(&***db, submission.id).await?;
+        trace!(
+            "submission {} enqueued at position {:?}",
+            submission.id,
+            position
+        );
+
+        CreateSubmission::ok(Submission::from(&submission, None, position))
+    }
+}
+
+response!(GetQueueStatus = {
+    Ok(200) => QueueStatus,
+});
+
+response!(ListSubmissions = {
+    Ok(200) => Vec<Submission>,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+});
+
+response!(GetSubmission = {
+    Ok(200) => SubmissionContent,
+    /// Submission does not exist.
+    SubmissionNotFound(404, error),
+});
+
+#[derive(ApiResponse)]
+enum GetSubmissionEvents {
+    /// A stream of server-sent events.
+    #[oai(status = 200)]
+    Ok(EventStream<BoxStream<'static, SubmissionEvent>>),
+    /// Submission does not exist.
+    #[oai(status = 404)]
+    SubmissionNotFound(Json<SubmissionNotFoundError>),
+}
+
+static_string!(SubmissionNotFoundText, "submission_not_found");
+
+#[derive(Debug, Default, Object)]
+struct SubmissionNotFoundError {
+    error: SubmissionNotFoundText,
+}
+
+impl GetSubmissionEvents {
+    fn submission_not_found<A>() -> poem_ext::responses::Response<Self, A> {
+        Ok(Self::SubmissionNotFound(Json(Default::default())).into())
+    }
+}
+
+response!(ListSubmissionTests = {
+    Ok(200) => Vec<TestResult>,
+    /// Submission does not exist.
+    SubmissionNotFound(404, error),
+});
+
+response!(ListAllSubmissions = {
+    Ok(200) => Vec<Submission>,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// The user is neither an admin nor the creator of this coding challenge.
+    Forbidden(403, error),
+});
+
+response!(GetAnySubmission = {
+    Ok(200) => SubmissionContent,
+    /// Submission does not exist.
+    SubmissionNotFound(404, error),
+    /// The user is neither an admin nor the creator of this coding challenge.
+    Forbidden(403, error),
+});
+
+response!(ShareSubmission = {
+    Ok(200) => Submission,
+    /// Submission does not exist.
+    SubmissionNotFound(404, error),
+    /// Only accepted submissions can be shared.
+    NotAccepted(403, error),
+});
+
+response!(ListSharedSolutions = {
+    Ok(200) => Vec<SharedSolution>,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// The user has not solved the coding challenge yet.
+    NotSolved(403, error),
+});
+
+response!(ReplaySubmission = {
+    Ok(200) => ReplayResult,
+    /// Submission does not exist.
+    SubmissionNotFound(404, error),
+    /// The submission has not failed on any test case and no seed has been
+    /// specified.
+    NoFailedTest(404, error),
+    /// The user is neither an admin nor the creator of this coding challenge.
+    Forbidden(403, error),
+    /// Environment does not exist.
+    EnvironmentNotFound(404, error),
+    /// The evaluator failed to execute.
+    EvaluatorFailed(400, error),
+});
+
+response!(GetRejudgeStatus = {
+    Ok(200) => RejudgeStatus,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+});
+
+response!(Rejudge = {
+    Ok(200) => RejudgeStatus,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+});
+
+response!(ListInternalErrors = {
+    Ok(200) => Vec<Submission>,
+});
+
+response!(RetrySubmission = {
+    Ok(200) => Submission,
+    /// Submission does not exist.
+    SubmissionNotFound(404, error),
+    /// The submission is already waiting to be judged.
+    AlreadyQueued(409, error),
+    /// The latest result of the submission is not an internal error.
+    NoInternalError(409, error),
+});
+
+response!(CreateSubmission = {
+    Ok(201) => Submission,
+    /// Try again later. `details` contains the number of seconds to wait.
+    TooManyRequests(429, error) => u64,
+    /// The user already has too many submissions that are waiting to be judged.
+    TooManyPendingSubmissions(429, error),
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// The solution environment does not exist.
+    EnvironmentNotFound(404, error),
+    /// The names of the submitted files are not unique.
+    DuplicateFileName(400, error),
+    /// The user has already submitted identical code for the current version of this coding challenge and the existing submission is pending or did not time out or fail with an internal error. `details` contains the id of the existing submission.
+    DuplicateSubmission(409, error) => Uuid,
+    /// The user is currently banned from submitting solutions.
+    Banned(403, error) => Option<DateTime<Utc>>,
+    /// The user does not have enough hearts to submit a solution and is neither an admin nor the creator of this subtask.
+    NotEnoughHearts(403, error),
+});
+
+struct JudgeWorker {
+    id: Uuid,
+    state: Arc<SharedState>,
+    config: Arc<Config>,
+    sandkasten: SandkastenClient,
+    judge_cache: Cache<JsonFormatter>,
+    submission_events: SubmissionEvents,
+}
+
+impl JudgeWorker {
+    async fn start(self) -> Result<(), DbErr> {
+        let concurrency = self.config.challenges.coding_challenges.max_concurrency;
+        judge_queue::heartbeat(
+            &self.state.db,
+            self.id,
+            concurrency,
+            lease_duration(&self.config),
+        )
+        .await?;
+        debug!(
+            "started judge worker {} with {concurrency} task(s)",
+            self.id
+        );
+
+        let worker = Arc::new(self);
+        tokio::spawn(Arc::clone(&worker).heartbeat());
+        for _ in 0..concurrency {
+            tokio::spawn(Arc::clone(&worker).run());
+        }
+        Ok(())
+    }
+
+    async fn heartbeat(self: Arc<Self>) {
+        let concurrency = self.config.challenges.coding_challenges.max_concurrency;
+        let lease_duration = lease_duration(&self.config);
+        // `interval` panics if the period is zero
+        let period = (lease_duration.to_std().unwrap_or_default() / 3).max(Duration::from_secs(1));
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            if let Err(err) =
+                judge_queue::heartbeat(&self.state.db, self.id, concurrency, lease_duration).await
+            {
+                error!("judge worker {} failed to send heartbeat: {err}", self.id);
+            }
+        }
+    }
+
+    async fn run(self: Arc<Self>) {
+        let poll_interval =
+            Duration::from_secs(self.config.challenges.coding_challenges.poll_interval);
+        loop {
+            match judge_queue::lease(&self.state.db, self.id, lease_duration(&self.config)).await {
+                Ok(Some(job)) => {
+                    self.submission_events.publish_queue_changed().await;
+                    self.process(job).await;
+                }
+                Ok(None) => tokio::time::sleep(poll_interval).await,
+                Err(err) => {
+                    error!("judge worker {} failed to lease job: {err}", self.id);
+                    tokio::time::sleep(poll_interval).await;
+                }
+            }
+        }
+    }
+
+    async fn process(&self, job: challenges_coding_challenge_judge_jobs::Model) {
+        let config = &self.config.challenges.coding_challenges;
+        let submission_id = job.submission_id;
+        let attempts = job.attempts as u32 + 1;
+        let rejudge = job.rejudge;
+        trace!("judge worker {} leased {submission_id}", self.id);
+        let Err(err) = self.judge(job).await else {
+            return;
+        };
+        error!("judge task for {submission_id} failed (attempt {attempts}): {err}");
 
-                if submission.creator != subtask.creator {
-                    send_task_rewards(&state.services, db, submission.creator, subtask).await?;
+        if !err.is_transient() || attempts >= config.max_judge_attempts {
+            match self.give_up(submission_id, rejudge).await {
+                Ok(()) => {
+                    self.submission_events.publish_queue_changed().await;
+                    return;
                 }
+                Err(err) => error!("judge task for {submission_id} failed to give up: {err}"),
             }
-            challenges_coding_challenge_result::ActiveModel {
-                submission_id: Set(submission.id),
-                verdict: Set(ChallengesVerdict::Ok),
-                reason: Set(None),
-                build_status: Set(None),
-                build_stderr: Set(None),
-                build_time: Set(None),
-                build_memory: Set(None),
-                run_status: Set(None),
-                run_stderr: Set(None),
-                run_time: Set(None),
-                run_memory: Set(None),
+        }
+
+        // exponential backoff
+        let backoff = config
+            .retry_backoff
+            .saturating_mul(1 << (attempts - 1).min(16));
+        if let Err(err) = judge_queue::release(
+            &self.state.db,
+            submission_id,
+            self.id,
+            chrono::Duration::seconds(backoff as _),
+        )
+        .await
+        {
+            error!("judge task for {submission_id} failed to release job: {err}");
+        }
+        self.submission_events.publish_queue_changed().await;
+    }
+
+    /// Stop trying to judge a submission. The submission receives an
+    /// `INTERNAL_ERROR` result and the hearts the user paid for it are
+    /// refunded, unless the job is a rejudge.
+    async fn give_up(
+        &self,
+        submission_id: Uuid,
+        rejudge: bool,
+    ) -> Result<(), JudgeSubmissionError> {
+        let Some((submission, result)) =
+            give_up_job(&self.state.db, submission_id, self.id, rejudge).await?
+        else {
+            return Ok(());
+        };
+
+        // the hearts are refunded only after the commit, so that they cannot be
+        // refunded again if the commit fails and the job is retried
+        if submission.hearts > 0 {
+            if let Err(err) = self
+                .state
+                .services
+                .shop
+                .add_hearts(submission.creator, submission.hearts)
+                .await
+            {
+                error!(
+                    "failed to refund {} heart(s) to {} for submission {}: {err}",
+                    submission.hearts, submission.creator, submission.id
+                );
             }
-            .insert(db)
-            .await?;
         }
-        Err(CheckError::TestcaseFailed(CheckTestcaseError { result, .. })) => {
-            let (build_status, build_stderr, build_time, build_memory) = match result.compile {
+
+        JUDGE_VERDICTS.inc(&[&result.verdict.to_value()]);
+        self.submission_events
+            .publish(submission.id, &SubmissionEvent::Result(result.into()))
+            .await;
+        Ok(())
+    }
+
+    async fn judge(
+        &self,
+        job: challenges_coding_challenge_judge_jobs::Model,
+    ) -> Result<(), JudgeSubmissionError> {
+        let db = &self.state.db;
+        let submission_id = job.submission_id;
+        let Some((submission, Some(cc))) =
+            challenges_coding_challenge_submissions::Entity::find_by_id(submission_id)
+                .find_also_related(challenges_coding_challenges::Entity)
+                .one(db)
+                .await?
+        else {
+            return Err(JudgeSubmissionError::SubmissionNotFound(submission_id));
+        };
+        let Some(subtask) = challenges_subtasks::Entity::find_by_id(cc.subtask_id)
+            .one(db)
+            .await?
+        else {
+            return Err(JudgeSubmissionError::SubmissionNotFound(submission_id));
+        };
+
+        judge_submission(JudgeSubmission {
+            db,
+            worker: self.id,
+            rejudge: job.rejudge,
+            subtask: &subtask,
+            challenge: &cc,
+            submission: &submission,
+            judge: Judge::new(
+                &self.sandkasten,
+                &self.judge_cache,
+                &self.config.challenges.coding_challenges,
+                &cc,
+            ),
+            state: &self.state,
+            config: &self.config,
+            submission_events: &self.submission_events,
+        })
+        .await
+    }
+}
+
+/// Complete the job of a submission that could not be judged. The submission
+/// receives an `INTERNAL_ERROR` result and its hearts are reset, so that they
+/// can be refunded. Rejudges keep the earlier verdict of the submission and are
+/// not refunded again. Return the submission with the hearts to refund and the
+/// new result.
+async fn give_up_job(
+    db: &impl TransactionTrait,
+    submission_id: Uuid,
+    worker: Uuid,
+    rejudge: bool,
+) -> Result<
+    Option<(
+        challenges_coding_challenge_submissions::Model,
+        challenges_coding_challenge_result::Model,
+    )>,
+    JudgeSubmissionError,
+> {
+    let txn = db.begin().await?;
+    if !judge_queue::complete(&txn, submission_id, worker).await? {
+        return Err(JudgeSubmissionError::LeaseLost);
+    }
+    // rejudged submissions keep their earlier verdict and have already been
+    // paid for or refunded
+    if rejudge {
+        txn.commit().await?;
+        return Ok(None);
+    }
+    let Some(submission) =
+        challenges_coding_challenge_submissions::Entity::find_by_id(submission_id)
+            .one(&txn)
+            .await?
+    else {
+        txn.commit().await?;
+        return Ok(None);
+    };
+
+    // submissions that are retried and fail again are not refunded twice
+    if submission.hearts > 0 {
+        challenges_coding_challenge_submissions::ActiveModel {
+            id: Unchanged(submission.id),
+            hearts: Set(0),
+            ..Default::default()
+        }
+        .update(&txn)
+        .await?;
+    }
+
+    let result = challenges_coding_challenge_result::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        submission_id: Set(submission.id),
+        creation_timestamp: Set(Utc::now().naive_utc()),
+        verdict: Set(ChallengesVerdict::InternalError),
+        reason: Set(None),
+        build_status: Set(None),
+        build_stderr: Set(None),
+        build_time: Set(None),
+        build_memory: Set(None),
+        run_status: Set(None),
+        run_stderr: Set(None),
+        run_time: Set(None),
+        run_memory: Set(None),
+        score: Set(0.0),
+        evaluator_id: Set(None),
+        failed_seed: Set(None),
+        failed_index: Set(None),
+        failed_input: Set(None),
+        failed_expected_output: Set(None),
+    }
+    .insert(&txn)
+    .await?;
+    txn.commit().await?;
+
+    Ok(Some((submission, result)))
+}
+
+pub fn lease_duration(config: &Config) -> chrono::Duration {
+    chrono::Duration::seconds(config.challenges.coding_challenges.lease_duration as _)
+}
+
+struct JudgeSubmission<'a, 'b> {
+    db: &'a DatabaseConnection,
+    worker: Uuid,
+    rejudge: bool,
+    subtask: &'a challenges_subtasks::Model,
+    challenge: &'a challenges_coding_challenges::Model,
+    submission: &'a challenges_coding_challenge_submissions::Model,
+    judge: Judge<'b>,
+    state: &'a SharedState,
+    config: &'a Config,
+    submission_events: &'a SubmissionEvents,
+}
+
+async fn judge_submission(
+    JudgeSubmission {
+        db,
+        worker,
+        rejudge,
+        subtask,
+        challenge,
+        submission,
+        judge,
+        state,
+        config,
+        submission_events,
+    }: JudgeSubmission<'_, '_>,
+) -> Result<(), JudgeSubmissionError> {
+    debug!("judging submission {}", submission.id);
+    let start = Instant::now();
+    let files = get_submission_files(db, submission.id).await?;
+    let test_data = get_test_data(db, challenge.subtask_id).await?;
+    let hash = hash_submission(
+        &judge,
+        challenge,
+        &test_data,
+        &submission.environment,
+        &submission.code,
+        &files,
+    )
+    .await?;
+    let verdict_cache = get_verdict_cache(db, &hash).await?;
+    let (progress_tx, mut progress_rx) = mpsc::unbounded();
+    let verdict_cache = &verdict_cache;
+    let check = async move {
+        let on_progress = move |test, total| {
+            let _ = progress_tx.unbounded_send(SubmissionProgress { test, total });
+        };
+        run_testcases(CheckChallenge {
+            judge,
+            challenge_id: challenge.subtask_id,
+            solution_environment: &submission.environment,
+            solution_code: &submission.code,
+            solution_files: &files,
+            time_limit: challenge.time_limit as _,
+            memory_limit: challenge.memory_limit as _,
+            static_tests: challenge.static_tests as _,
+            test_data: &test_data,
+            random_tests: challenge.random_tests as _,
+            test_concurrency: config.challenges.coding_challenges.test_concurrency,
+            scoring_mode: challenge.scoring_mode,
+            on_progress: Some(&on_progress),
+            verdict_cache: Some(verdict_cache),
+        })
+        .await
+    };
+    let publish_progress = async move {
+        while let Some(progress) = progress_rx.next().await {
+            submission_events
+                .publish(submission.id, &SubmissionEvent::Progress(progress))
+                .await;
+        }
+    };
+    let (testcases, ()) = futures::join!(check, publish_progress);
+    let testcases = match testcases? {
+        Ok(testcases) => testcases,
+        Err(err) => return Err(JudgeSubmissionError::Check(Box::new(err))),
+    };
+    trace!("judge result for {}: {testcases:?}", submission.id);
+
+    // examples are public, so users may see the input and the expected output
+    // of a failed example
+    let failed = testcases
+        .iter()
+        .enumerate()
+        .find(|(_, testcase)| testcase.result.verdict != ChallengesVerdict::Ok);
+    let failed_example = match failed {
+        Some((_, testcase)) if judge.examples().await?.contains(&testcase.seed) => judge
+            .get_example_checked(
+                &testcase.seed,
+                &challenge.solution_environment,
+                &challenge.solution_code,
+                &[],
+                Some(challenge.time_limit as _),
+                Some(challenge.memory_limit as _),
+            )
+            .await?
+            .ok(),
+        _ => None,
+    };
+
+    let txn = db.begin().await?;
+    let db = &txn;
+    if !judge_queue::complete(db, submission.id, worker).await? {
+        return Err(JudgeSubmissionError::LeaseLost);
+    }
+    lock_user_subtask(db, submission.creator, subtask.id).await?;
+    let user_subtask = get_user_subtask(db, submission.creator, subtask.id).await?;
+
+    // users receive the share of the rewards that corresponds to the
+    // improvement over their best previous score
+    let score = score(challenge.scoring_mode, &testcases);
+    if submission.creator != subtask.creator {
+        let previous_score = get_best_score(db, submission.creator, subtask.id).await?;
+        send_partial_task_rewards(
+            &state.services,
+            db,
+            submission.creator,
+            subtask,
+            previous_score,
+            score,
+        )
+        .await?;
+    }
+
+    let result_id = Uuid::new_v4();
+    let result = match failed {
+        Some((index, Testcase { seed, result, .. })) => {
+            let (build_status, build_stderr, build_time, build_memory) = match &result.compile {
                 Some(x) => (
                     Some(x.status),
-                    Some(x.stderr),
+                    Some(x.stderr.clone()),
                     Some(x.resource_usage.time as _),
                     Some(x.resource_usage.memory as _),
                 ),
                 None => (None, None, None, None),
             };
-            let (run_status, run_stderr, run_time, run_memory) = match result.run {
+            let (run_status, run_stderr, run_time, run_memory) = match &result.run {
                 Some(x) => (
                     Some(x.status),
-                    Some(x.stderr),
+                    Some(x.stderr.clone()),
                     Some(x.resource_usage.time as _),
                     Some(x.resource_usage.memory as _),
                 ),
                 None => (None, None, None, None),
             };
-            update_user_subtask(
-                db,
-                user_subtask.as_ref(),
-                challenges_user_subtasks::ActiveModel {
-                    user_id: Set(submission.creator),
-                    subtask_id: Set(subtask.id),
-                    last_attempt_timestamp: Set(Some(submission.creation_timestamp)),
-                    attempts: Set(user_subtask.attempts() as i32 + 1),
-                    ..Default::default()
-                },
-            )
-            .await?;
+            if !rejudge {
+                update_user_subtask(
+                    db,
+                    user_subtask.as_ref(),
+                    challenges_user_subtasks::ActiveModel {
+                        user_id: Set(submission.creator),
+                        subtask_id: Set(subtask.id),
+                        last_attempt_timestamp: Set(Some(submission.creation_timestamp)),
+                        attempts: Set(user_subtask.attempts() as i32 + 1),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+            }
             challenges_coding_challenge_result::ActiveModel {
+                id: Set(result_id),
                 submission_id: Set(submission.id),
+                creation_timestamp: Set(Utc::now().naive_utc()),
                 verdict: Set(result.verdict),
-                reason: Set(result.reason),
+                reason: Set(result.reason.clone()),
                 build_status: Set(build_status),
                 build_stderr: Set(build_stderr),
                 build_time: Set(build_time),
@@ -454,16 +1583,465 @@ async fn judge_submission(
                 run_stderr: Set(run_stderr),
                 run_time: Set(run_time),
                 run_memory: Set(run_memory),
+                score: Set(score),
+                evaluator_id: Set(Some(challenge.evaluator_id)),
+                failed_seed: Set(Some(seed.clone())),
+                failed_index: Set(Some(index as _)),
+                failed_input: Set(failed_example.as_ref().map(|example| example.input.clone())),
+                failed_expected_output: Set(failed_example.map(|example| example.output)),
             }
-            .insert(db)
-            .await?;
         }
-        Err(err) => return Err(JudgeSubmissionError::Check(Box::new(err))),
+        None => {
+            if !user_subtask.is_solved() {
+                let values = if rejudge {
+                    challenges_user_subtasks::ActiveModel {
+                        user_id: Set(submission.creator),
+                        subtask_id: Set(subtask.id),
+                        solved_timestamp: Set(Some(submission.creation_timestamp)),
+                        ..Default::default()
+                    }
+                } else {
+                    challenges_user_subtasks::ActiveModel {
+                        user_id: Set(submission.creator),
+                        subtask_id: Set(subtask.id),
+                        solved_timestamp: Set(Some(submission.creation_timestamp)),
+                        last_attempt_timestamp: Set(Some(submission.creation_timestamp)),
+                        attempts: Set(user_subtask.attempts() as i32 + 1),
+                        ..Default::default()
+                    }
+                };
+                update_user_subtask(db, user_subtask.as_ref(), values).await?;
+            }
+            // accepted submissions are ranked by the maximum resource usage
+            // of a single test case
+            let max_usage = |summary: fn(&Testcase) -> Option<&ResourceUsage>| {
+                let usage = testcases.iter().filter_map(summary);
+                (
+                    usage.clone().map(|x| x.time).max().map(|x| x as _),
+                    usage.map(|x| x.memory).max().map(|x| x as _),
+                )
+            };
+            let (build_time, build_memory) =
+                max_usage(|testcase| Some(&testcase.result.compile.as_ref()?.resource_usage));
+            let (run_time, run_memory) =
+                max_usage(|testcase| Some(&testcase.result.run.as_ref()?.resource_usage));
+            challenges_coding_challenge_result::ActiveModel {
+                id: Set(result_id),
+                submission_id: Set(submission.id),
+                creation_timestamp: Set(Utc::now().naive_utc()),
+                verdict: Set(ChallengesVerdict::Ok),
+                reason: Set(None),
+                build_status: Set(None),
+                build_stderr: Set(None),
+                build_time: Set(build_time),
+                build_memory: Set(build_memory),
+                run_status: Set(None),
+                run_stderr: Set(None),
+                run_time: Set(run_time),
+                run_memory: Set(run_memory),
+                score: Set(score),
+                evaluator_id: Set(Some(challenge.evaluator_id)),
+                failed_seed: Set(None),
+                failed_index: Set(None),
+                failed_input: Set(None),
+                failed_expected_output: Set(None),
+            }
+        }
+    };
+    let result = result.insert(db).await?;
+
+    if !testcases.is_empty() {
+        challenges_coding_challenge_test_result::Entity::insert_many(
+            testcases
+                .iter()
+                .enumerate()
+                .map(|(i, testcase)| test_result_model(result_id, i, testcase)),
+        )
+        .exec(db)
+        .await?;
+    }
+
+    // execution times vary, so time limits exceeded are not reused
+    let cacheable = testcases
+        .iter()
+        .filter(|testcase| {
+            testcase.deterministic
+                && testcase.result.verdict != ChallengesVerdict::TimeLimitExceeded
+                && !verdict_cache.contains_key(&testcase.seed)
+        })
+        .map(|testcase| {
+            Ok(challenges_coding_challenge_verdict_cache::ActiveModel {
+                hash: Set(hash.clone()),
+                seed: Set(testcase.seed.clone()),
+                challenge_id: Set(challenge.subtask_id),
+                creation_timestamp: Set(Utc::now().naive_utc()),
+                result: Set(serde_json::to_value(&testcase.result)?),
+            })
+        })
+        .collect::<Result<Vec<_>, serde_json::Error>>()?;
+    if !cacheable.is_empty() {
+        challenges_coding_challenge_verdict_cache::Entity::insert_many(cacheable)
+            .on_conflict(
+                OnConflict::columns([
+                    challenges_coding_challenge_verdict_cache::Column::Hash,
+                    challenges_coding_challenge_verdict_cache::Column::Seed,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .do_nothing()
+            .exec(db)
+            .await?;
     }
 
+    txn.commit().await?;
+    JUDGE_VERDICTS.inc(&[&result.verdict.to_value()]);
+    JUDGE_DURATION.observe(&[], start.elapsed().as_secs_f64());
+    submission_events
+        .publish(submission.id, &SubmissionEvent::Result(result.into()))
+        .await;
     Ok(())
 }
 
+fn test_result_model(
+    result_id: Uuid,
+    index: usize,
+    testcase: &Testcase,
+) -> challenges_coding_challenge_test_result::ActiveModel {
+    let run = testcase.result.run.as_ref();
+    challenges_coding_challenge_test_result::ActiveModel {
+        result_id: Set(result_id),
+        index: Set(index as _),
+        seed: Set(testcase.seed.clone()),
+        verdict: Set(testcase.result.verdict),
+        reason: Set(testcase.result.reason.clone()),
+        run_status: Set(run.map(|x| x.status)),
+        run_time: Set(run.map(|x| x.resource_usage.time as _)),
+        run_memory: Set(run.map(|x| x.resource_usage.memory as _)),
+    }
+}
+
+/// Return the latest result of each of the given submissions.
+async fn get_latest_results(
+    db: &impl ConnectionTrait,
+    submission_ids: impl IntoIterator<Item = Uuid>,
+) -> Result<HashMap<Uuid, challenges_coding_challenge_result::Model>, DbErr> {
+    Ok(challenges_coding_challenge_result::Entity::find()
+        .filter(challenges_coding_challenge_result::Column::SubmissionId.is_in(submission_ids))
+        .order_by_asc(challenges_coding_challenge_result::Column::CreationTimestamp)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|result| (result.submission_id, result))
+        .collect())
+}
+
+/// Select the results of the same submission that are newer than the result
+/// of the base query.
+fn get_newer_result_query() -> SelectStatement {
+    let newer = Alias::new("newer");
+    sea_query::Query::select()
+        .expr(Expr::val(1))
+        .from_as(challenges_coding_challenge_result::Entity, newer.clone())
+        .and_where(
+            Expr::col((
+                newer.clone(),
+                challenges_coding_challenge_result::Column::SubmissionId,
+            ))
+            .equals((
+                challenges_coding_challenge_result::Entity,
+                challenges_coding_challenge_result::Column::SubmissionId,
+            )),
+        )
+        .and_where(
+            Expr::col((
+                newer,
+                challenges_coding_challenge_result::Column::CreationTimestamp,
+            ))
+            .gt(Expr::col((
+                challenges_coding_challenge_result::Entity,
+                challenges_coding_challenge_result::Column::CreationTimestamp,
+            ))),
+        )
+        .to_owned()
+}
+
+/// Select the maximum value of a column of the test results of the result of
+/// the base query, which is `NULL` if there are no test results.
+fn get_max_test_result_query(
+    column: challenges_coding_challenge_test_result::Column,
+) -> SimpleExpr {
+    SimpleExpr::SubQuery(
+        None,
+        Box::new(
+            sea_query::Query::select()
+                .expr(column.max())
+                .from(challenges_coding_challenge_test_result::Entity)
+                .and_where(
+                    Expr::col((
+                        challenges_coding_challenge_test_result::Entity,
+                        challenges_coding_challenge_test_result::Column::ResultId,
+                    ))
+                    .equals((
+                        challenges_coding_challenge_result::Entity,
+                        challenges_coding_challenge_result::Column::Id,
+                    )),
+                )
+                .to_owned()
+                .into_sub_query_statement(),
+        ),
+    )
+}
+
+/// Return the hash of a submission for the current revision of a coding
+/// challenge.
+async fn hash_submission(
+    judge: &Judge<'_>,
+    cc: &challenges_coding_challenges::Model,
+    test_data: &[challenges_coding_challenge_test_data::Model],
+    environment: &str,
+    code: &str,
+    files: &[File],
+) -> Result<String, judge::Error> {
+    let (time_limit, memory_limit) = judge
+        .effective_limits(
+            environment,
+            Some(cc.time_limit as _),
+            Some(cc.memory_limit as _),
+        )
+        .await?;
+    Ok(judge::submission_hash(
+        cc.evaluator_id,
+        Checker::from(cc),
+        environment,
+        code,
+        files,
+        (
+            time_limit.unwrap_or_default(),
+            memory_limit.unwrap_or_default(),
+        ),
+        (cc.static_tests as _, test_data, cc.random_tests as _),
+    ))
+}
+
+/// Return the cached results of the examples and static tests of submissions
+/// with the given hash, keyed by seed.
+async fn get_verdict_cache(
+    db: &impl ConnectionTrait,
+    hash: &str,
+) -> Result<HashMap<String, CheckResult<RunResult>>, DbErr> {
+    Ok(challenges_coding_challenge_verdict_cache::Entity::find()
+        .filter(challenges_coding_challenge_verdict_cache::Column::Hash.eq(hash))
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|entry| Some((entry.seed, serde_json::from_value(entry.result).ok()?)))
+        .collect())
+}
+
+/// Return the maximum run time and memory usage of a single test case for
+/// each of the given results.
+async fn get_max_resource_usage(
+    db: &impl ConnectionTrait,
+    result_ids: impl IntoIterator<Item = Uuid>,
+) -> Result<HashMap<Uuid, (Option<i32>, Option<i32>)>, DbErr> {
+    Ok(challenges_coding_challenge_test_result::Entity::find()
+        .select_only()
+        .column(challenges_coding_challenge_test_result::Column::ResultId)
+        .column_as(
+            challenges_coding_challenge_test_result::Column::RunTime.max(),
+            "run_time",
+        )
+        .column_as(
+            challenges_coding_challenge_test_result::Column::RunMemory.max(),
+            "run_memory",
+        )
+        .filter(challenges_coding_challenge_test_result::Column::ResultId.is_in(result_ids))
+        .group_by(challenges_coding_challenge_test_result::Column::ResultId)
+        .into_tuple::<(Uuid, Option<i32>, Option<i32>)>()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|(result_id, time, memory)| (result_id, (time, memory)))
+        .collect())
+}
+
+/// Return the additional files of each of the given submissions.
+async fn get_files_of_submissions(
+    db: &impl ConnectionTrait,
+    submission_ids: impl IntoIterator<Item = Uuid>,
+) -> Result<HashMap<Uuid, Vec<File>>, DbErr> {
+    let mut files = HashMap::<_, Vec<_>>::new();
+    for file in challenges_coding_challenge_submission_files::Entity::find()
+        .filter(
+            challenges_coding_challenge_submission_files::Column::SubmissionId
+                .is_in(submission_ids),
+        )
+        .order_by_asc(challenges_coding_challenge_submission_files::Column::Name)
+        .all(db)
+        .await?
+    {
+        files.entry(file.submission_id).or_default().push(File {
+            name: file.name,
+            content: file.content,
+        });
+    }
+    Ok(files)
+}
+
+async fn get_submission_files(
+    db: &impl ConnectionTrait,
+    submission_id: Uuid,
+) -> Result<Vec<File>, DbErr> {
+    Ok(challenges_coding_challenge_submission_files::Entity::find()
+        .filter(
+            challenges_coding_challenge_submission_files::Column::SubmissionId.eq(submission_id),
+        )
+        .order_by_asc(challenges_coding_challenge_submission_files::Column::Name)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|file| File {
+            name: file.name,
+            content: file.content,
+        })
+        .collect())
+}
+
+/// Return the best score of all results of a user's submissions for a coding
+/// challenge, or `0` if the user has no judged submissions.
+async fn get_best_score(
+    db: &impl ConnectionTrait,
+    user_id: Uuid,
+    subtask_id: Uuid,
+) -> Result<f64, DbErr> {
+    Ok(challenges_coding_challenge_result::Entity::find()
+        .inner_join(challenges_coding_challenge_submissions::Entity)
+        .filter(challenges_coding_challenge_submissions::Column::Creator.eq(user_id))
+        .filter(challenges_coding_challenge_submissions::Column::SubtaskId.eq(subtask_id))
+        .order_by_desc(challenges_coding_challenge_result::Column::Score)
+        .one(db)
+        .await?
+        .map_or(0.0, |result| result.score))
+}
+
+/// Return the stream of events of a submission, starting with its current
+/// state.
+async fn submission_event_stream(
+    db: DatabaseConnection,
+    submission_id: Uuid,
+    messages: impl Stream<Item = Message> + Send + 'static,
+) -> Result<BoxStream<'static, SubmissionEvent>, DbErr> {
+    let Some(position) = judge_queue::position(&db, submission_id).await? else {
+        let result = get_latest_results(&db, [submission_id])
+            .await?
+            .remove(&submission_id);
+        return Ok(
+            stream::iter(result.map(|result| SubmissionEvent::Result(result.into()))).boxed(),
+        );
+    };
+
+    let first = SubmissionEvent::QueuePosition(SubmissionQueuePosition { position });
+    let updates = stream::unfold(
+        (messages.boxed(), Some(position)),
+        move |(mut messages, mut position)| {
+            let db = db.clone();
+            async move {
+                // the position is reset after the result has been sent
+                position?;
+                loop {
+                    let event = match messages.next().await? {
+                        Message::Event(event) => event,
+                        Message::QueueChanged => {
+                            match judge_queue::position(&db, submission_id).await {
+                                Ok(Some(new)) if Some(new) == position => continue,
+                                Ok(Some(new)) => {
+                                    position = Some(new);
+                                    SubmissionEvent::QueuePosition(SubmissionQueuePosition {
+                                        position: new,
+                                    })
+                                }
+                                Ok(None) => match get_latest_results(&db, [submission_id]).await {
+                                    Ok(mut results) => match results.remove(&submission_id) {
+                                        Some(result) => SubmissionEvent::Result(result.into()),
+                                        None => continue,
+                                    },
+                                    Err(err) => {
+                                        error!("failed to load result of {submission_id}: {err}");
+                                        return None;
+                                    }
+                                },
+                                Err(err) => {
+                                    error!(
+                                        "failed to load queue position of {submission_id}: {err}"
+                                    );
+                                    return None;
+                                }
+                            }
+                        }
+                    };
+                    if matches!(event, SubmissionEvent::Result(_)) {
+                        position = None;
+                    }
+                    return Some((event, (messages, position)));
+                }
+            }
+        },
+    );
+
+    Ok(stream::once(async { first }).chain(updates).boxed())
+}
+
+async fn get_rejudge_status(
+    db: &impl ConnectionTrait,
+    cc: &challenges_coding_challenges::Model,
+    subtask: &challenges_subtasks::Model,
+) -> Result<RejudgeStatus, DbErr> {
+    let submissions = cc
+        .find_related(challenges_coding_challenge_submissions::Entity)
+        .all(db)
+        .await?;
+    let results =
+        get_latest_results(db, submissions.iter().map(|submission| submission.id)).await?;
+    let pending = challenges_coding_challenge_judge_jobs::Entity::find()
+        .filter(
+            challenges_coding_challenge_judge_jobs::Column::SubmissionId
+                .is_in(submissions.iter().map(|submission| submission.id)),
+        )
+        .all(db)
+        .await?;
+    let pending_rejudge = pending.iter().filter(|job| job.rejudge).count() as u64;
+    let pending = pending
+        .into_iter()
+        .map(|job| job.submission_id)
+        .collect::<HashSet<_>>();
+
+    let mut passing = HashMap::<Uuid, bool>::new();
+    for submission in &submissions {
+        let ok = pending.contains(&submission.id)
+            || results
+                .get(&submission.id)
+                .is_some_and(|result| result.verdict == ChallengesVerdict::Ok);
+        *passing.entry(submission.creator).or_default() |= ok;
+    }
+
+    let mut no_longer_passing = subtask
+        .find_related(challenges_user_subtasks::Entity)
+        .filter(challenges_user_subtasks::Column::SolvedTimestamp.is_not_null())
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|user_subtask| user_subtask.user_id)
+        .filter(|user_id| passing.get(user_id) == Some(&false))
+        .collect::<Vec<_>>();
+    no_longer_passing.sort_unstable();
+
+    Ok(RejudgeStatus {
+        pending: pending_rejudge,
+        no_longer_passing,
+    })
+}
+
 #[derive(Debug, Error)]
 enum JudgeSubmissionError {
     #[error("failed to judge submission: {0}")]
@@ -474,6 +2052,23 @@ enum JudgeSubmissionError {
     Check(Box<CheckError>),
     #[error("could not send task rewards: {0}")]
     TaskRewards(#[from] SendTaskRewardsError),
+    #[error("submission {0} does not exist")]
+    SubmissionNotFound(Uuid),
+    #[error("lease has expired")]
+    LeaseLost,
+    #[error("serde_json error: {0}")]
+    SerdeJson(#[from] serde_json::Error),
+}
+
+impl JudgeSubmissionError {
+    /// Return whether judging the submission again may succeed.
+    fn is_transient(&self) -> bool {
+        match self {
+            Self::Judge(err) => err.is_transient(),
+            Self::Db(_) | Self::TaskRewards(_) | Self::LeaseLost => true,
+            Self::Check(_) | Self::SubmissionNotFound(_) | Self::SerdeJson(_) => false,
+        }
+    }
 }
 
 impl Api {
@@ -487,198 +2082,131 @@ impl Api {
     }
 
     pub async fn setup_api(self) -> anyhow::Result<Self> {
-        self.resume_judge()
-            .await
-            .context("failed to resume judge")?;
+        JudgeWorker {
+            id: Uuid::new_v4(),
+            state: Arc::clone(&self.state),
+            config: Arc::clone(&self.config),
+            sandkasten: self.sandkasten.clone(),
+            judge_cache: self.judge_cache.clone(),
+            submission_events: self.submission_events.clone(),
+        }
+        .start()
+        .await
+        .context("failed to start judge worker")?;
         Ok(self)
     }
+}
 
-    pub async fn resume_judge(&self) -> anyhow::Result<()> {
-        debug!("resuming judge");
-        let db = &self.state.db;
+#[cfg(test)]
+mod tests {
+    use crate::services::judge_queue::tests::setup;
 
-        let subtasks = challenges_subtasks::Entity::find()
-            .all(db)
-            .await?
-            .into_iter()
-            .map(|x| (x.id, x))
-            .collect::<HashMap<_, _>>();
-        let coding_challenges = challenges_coding_challenges::Entity::find()
-            .all(db)
-            .await?
-            .into_iter()
-            .map(|x| (x.subtask_id, Arc::new(x)))
-            .collect::<HashMap<_, _>>();
-        let user_subtasks = challenges_user_subtasks::Entity::find()
-            .all(db)
-            .await?
-            .into_iter()
-            .map(|x| ((x.user_id, x.subtask_id), x))
-            .collect::<HashMap<_, _>>();
-        let submissions = challenges_coding_challenge_submissions::Entity::find()
-            .left_join(challenges_coding_challenge_result::Entity)
-            .filter(challenges_coding_challenge_result::Column::SubmissionId.is_null())
-            .order_by_asc(challenges_coding_challenge_submissions::Column::CreationTimestamp)
-            .all(db)
-            .await?;
+    use super::*;
 
-        debug!("found {} submission(s) to judge", submissions.len());
-        for submission in submissions {
-            let Some(subtask) = subtasks.get(&submission.subtask_id) else {
-                bail!(
-                    "failed to find subtask {} for submission {}",
-                    submission.subtask_id,
-                    submission.id
-                );
-            };
-            let Some(challenge) = coding_challenges.get(&submission.subtask_id) else {
-                bail!(
-                    "failed to find coding challenge {} for submission {}",
-                    submission.subtask_id,
-                    submission.id
-                );
-            };
-            let user_subtask = user_subtasks.get(&(submission.creator, submission.subtask_id));
-            start_judge_submission_task(StartJudgeSubmissionTask {
-                submission: Arc::new(submission),
-                subtask: subtask.clone(),
-                judge_lock: Arc::clone(&self.judge_lock),
-                db: db.clone(),
-                sandkasten: self.sandkasten.clone(),
-                cache: self.judge_cache.clone(),
-                reward_lock: Arc::clone(&self.reward_lock),
-                state: Arc::clone(&self.state),
-                challenge: Arc::clone(challenge),
-                user_subtask: user_subtask.cloned(),
-                queue_positions: Arc::clone(&self.queue_positions),
-            })
-            .await;
+    /// Create a submission that has been accepted and lease a job for it.
+    async fn leased_submission(
+        db: &impl TransactionTrait,
+        worker: Uuid,
+        rejudge: bool,
+    ) -> challenges_coding_challenge_submissions::Model {
+        let txn = db.begin().await.unwrap();
+        let submission = challenges_coding_challenge_submissions::ActiveModel {
+            id: Set(Uuid::from_u128(1)),
+            subtask_id: Set(Uuid::nil()),
+            creator: Set(Uuid::from_u128(1)),
+            creation_timestamp: Set(Utc::now().naive_utc()),
+            environment: Set("python".into()),
+            code: Set(String::new()),
+            hash: Set(None),
+            hearts: Set(2),
+            shared: Set(false),
         }
-
-        Ok(())
-    }
-}
-
-pub struct QueuePositions {
-    workers: usize,
-    counter: usize,
-    done: usize,
-    ids: HashMap<Uuid, usize>,
-}
-
-impl QueuePositions {
-    pub fn new(workers: usize) -> Self {
-        Self {
-            workers,
-            counter: 0,
-            done: 0,
-            ids: HashMap::new(),
+        .insert(&txn)
+        .await
+        .unwrap();
+        challenges_coding_challenge_result::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            submission_id: Set(submission.id),
+            creation_timestamp: Set(Utc::now().naive_utc()),
+            verdict: Set(ChallengesVerdict::Ok),
+            score: Set(1.0),
+            ..Default::default()
         }
-    }
-
-    pub fn workers(&self) -> usize {
-        self.workers
-    }
-
-    pub fn active(&self) -> usize {
-        self.workers.min(self.counter - self.done)
-    }
-
-    pub fn waiting(&self) -> usize {
-        self.id_position(self.counter)
-    }
-
-    pub fn push(&mut self, key: Uuid) -> usize {
-        let id = *self.ids.entry(key).or_insert_with(|| {
-            self.counter += 1;
-            self.counter
-        });
-        self.id_position(id)
-    }
-
-    pub fn pop(&mut self, key: Uuid) -> bool {
-        if !self
-            .ids
-            .get(&key)
-            .is_some_and(|&x| self.id_position(x) == 0)
-        {
-            return false;
+        .insert(&txn)
+        .await
+        .unwrap();
+        if rejudge {
+            judge_queue::enqueue_rejudge(&txn, std::slice::from_ref(&submission))
+                .await
+                .unwrap();
+        } else {
+            judge_queue::enqueue(&txn, &submission, ChallengesJudgePriority::Normal)
+                .await
+                .unwrap();
         }
+        txn.commit().await.unwrap();
 
-        self.ids.remove(&key);
-        self.done += 1;
-        true
+        let job = judge_queue::lease(db, worker, chrono::Duration::minutes(1))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!((job.submission_id, job.rejudge), (submission.id, rejudge));
+        submission
     }
 
-    pub fn position(&self, key: Uuid) -> Option<usize> {
-        let id = *self.ids.get(&key)?;
-        Some(self.id_position(id))
+    async fn latest_verdict(db: &impl ConnectionTrait, submission_id: Uuid) -> ChallengesVerdict {
+        get_latest_results(db, [submission_id]).await.unwrap()[&submission_id].verdict
     }
 
-    fn id_position(&self, id: usize) -> usize {
-        id.saturating_sub(self.workers + self.done)
+    #[tokio::test]
+    #[ignore = "requires a migrated PostgreSQL database in DATABASE_URL"]
+    async fn give_up_refunds_submission() {
+        let db = setup().await;
+        let worker = Uuid::nil();
+        let submission = leased_submission(&db, worker, false).await;
+
+        let (refund, result) = give_up_job(&db, submission.id, worker, false)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(refund.hearts, 2);
+        assert_eq!(result.verdict, ChallengesVerdict::InternalError);
+        assert_eq!(
+            latest_verdict(&db, submission.id).await,
+            ChallengesVerdict::InternalError
+        );
+        let submission = challenges_coding_challenge_submissions::Entity::find_by_id(submission.id)
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(submission.hearts, 0);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[tokio::test]
+    #[ignore = "requires a migrated PostgreSQL database in DATABASE_URL"]
+    async fn give_up_rejudge_keeps_verdict() {
+        let db = setup().await;
+        let worker = Uuid::nil();
+        let submission = leased_submission(&db, worker, true).await;
 
-    #[test]
-    fn queue_positions() {
-        let mut qp = QueuePositions::new(3);
-        assert_eq!(qp.workers(), 3);
-        let key = Uuid::from_u128;
-        assert_eq!((qp.active(), qp.waiting()), (0, 0));
-        qp.push(key(0));
-        assert_eq!((qp.active(), qp.waiting()), (1, 0));
-        qp.push(key(1));
-        assert_eq!((qp.active(), qp.waiting()), (2, 0));
-        qp.push(key(2));
-        assert_eq!((qp.active(), qp.waiting()), (3, 0));
-        qp.push(key(3));
-        assert_eq!((qp.active(), qp.waiting()), (3, 1));
-        qp.push(key(4));
-        assert_eq!((qp.active(), qp.waiting()), (3, 2));
-        qp.push(key(5));
-        assert_eq!((qp.active(), qp.waiting()), (3, 3));
-        assert_eq!(qp.position(key(0)), Some(0));
-        assert_eq!(qp.position(key(1)), Some(0));
-        assert_eq!(qp.position(key(2)), Some(0));
-        assert_eq!(qp.position(key(3)), Some(1));
-        assert_eq!(qp.position(key(4)), Some(2));
-        assert_eq!(qp.position(key(5)), Some(3));
-
-        // cannot pop pending keys
-        assert!(!qp.pop(key(3)));
-        assert!(!qp.pop(key(4)));
-        assert!(!qp.pop(key(5)));
-        assert_eq!((qp.active(), qp.waiting()), (3, 3));
-
-        assert!(qp.pop(key(1)));
-        assert_eq!(qp.position(key(0)), Some(0));
-        assert_eq!(qp.position(key(1)), None);
-        assert_eq!(qp.position(key(2)), Some(0));
-        assert_eq!(qp.position(key(3)), Some(0));
-        assert_eq!(qp.position(key(4)), Some(1));
-        assert_eq!(qp.position(key(5)), Some(2));
-        assert_eq!((qp.active(), qp.waiting()), (3, 2));
-        assert!(!qp.pop(key(1))); // already popped
-
-        assert!(qp.pop(key(2)));
-        assert_eq!(qp.position(key(0)), Some(0));
-        assert_eq!(qp.position(key(1)), None);
-        assert_eq!(qp.position(key(2)), None);
-        assert_eq!(qp.position(key(3)), Some(0));
-        assert_eq!(qp.position(key(4)), Some(0));
-        assert_eq!(qp.position(key(5)), Some(1));
-        assert_eq!((qp.active(), qp.waiting()), (3, 1));
-
-        assert_eq!(qp.push(key(6)), 2);
-        assert_eq!((qp.active(), qp.waiting()), (3, 2));
-        assert_eq!(qp.push(key(6)), 2); // push is idempotent
-        assert_eq!((qp.active(), qp.waiting()), (3, 2));
-        assert_eq!(qp.push(key(7)), 3);
-        assert_eq!((qp.active(), qp.waiting()), (3, 3));
+        assert!(give_up_job(&db, submission.id, worker, true)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            latest_verdict(&db, submission.id).await,
+            ChallengesVerdict::Ok
+        );
+        let submission = challenges_coding_challenge_submissions::Entity::find_by_id(submission.id)
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(submission.hearts, 2);
+        assert!(judge_queue::position(&db, submission.id)
+            .await
+            .unwrap()
+            .is_none());
     }
 }
@@ -0,0 +1,329 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use entity::{
+    challenges_coding_challenge_test_data, challenges_coding_challenges,
+    sea_orm_active_enums::ChallengesVerdict,
+};
+use fnct::format::JsonFormatter;
+use lib::{auth::VerifiedUserAuth, config::Config, Cache};
+use poem::web::Data;
+use poem_ext::{db::DbTxn, response};
+use poem_openapi::{param::Path, payload::Json, OpenApi};
+use sandkasten_client::SandkastenClient;
+use schemas::challenges::coding_challenges::{
+    CreateTestDataRequest, TestData, UpdateTestDataRequest,
+};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, ModelTrait, QueryFilter,
+    QueryOrder, Set, Unchanged,
+};
+use uuid::Uuid;
+
+use super::{_CheckError, CheckError, CheckTestcaseError};
+use crate::{
+    endpoints::Tags,
+    services::{
+        judge::{test_data_seed, Error as JudgeError, Judge},
+        subtasks::{query_subtask_admin, QuerySubtaskAdminError},
+    },
+};
+
+pub struct Api {
+    pub config: Arc<Config>,
+    pub sandkasten: SandkastenClient,
+    pub judge_cache: Cache<JsonFormatter>,
+}
+
+#[OpenApi(tag = "Tags::CodingChallenges")]
+impl Api {
+    /// List the fixed test cases of a coding challenge.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/test_data",
+        method = "get"
+    )]
+    async fn list_test_data(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> ListTestData::Response<VerifiedUserAuth> {
+        match query_subtask_admin::<challenges_coding_challenges::Entity, _>(
+            &db,
+            &auth.0,
+            task_id.0,
+            subtask_id.0,
+            |cc, _| cc,
+        )
+        .await?
+        {
+            Ok(_) => {}
+            Err(QuerySubtaskAdminError::NotFound) => return ListTestData::subtask_not_found(),
+            Err(QuerySubtaskAdminError::NoAccess) => return ListTestData::forbidden(),
+        };
+
+        ListTestData::ok(
+            get_test_data(&***db, subtask_id.0)
+                .await?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        )
+    }
+
+    /// Add a fixed test case to a coding challenge.
+    ///
+    /// The sample solution of the coding challenge has to pass the new test
+    /// case.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/test_data",
+        method = "post"
+    )]
+    async fn create_test_data(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        data: Json<CreateTestDataRequest>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> CreateTestData::Response<VerifiedUserAuth> {
+        let cc = match query_subtask_admin::<challenges_coding_challenges::Entity, _>(
+            &db,
+            &auth.0,
+            task_id.0,
+            subtask_id.0,
+            |cc, _| cc,
+        )
+        .await?
+        {
+            Ok(cc) => cc,
+            Err(QuerySubtaskAdminError::NotFound) => return CreateTestData::subtask_not_found(),
+            Err(QuerySubtaskAdminError::NoAccess) => return CreateTestData::forbidden(),
+        };
+
+        let test = challenges_coding_challenge_test_data::Model {
+            id: Uuid::new_v4(),
+            challenge_id: cc.subtask_id,
+            creation_timestamp: Utc::now().naive_utc(),
+            input: data.0.input,
+            output: data.0.output,
+            data: data.0.data,
+        };
+        if let Err(result) = self.check_test_data(&cc, &test).await? {
+            return Ok(_CheckError::Response::from(result).into());
+        }
+
+        let test = challenges_coding_challenge_test_data::ActiveModel {
+            id: Set(test.id),
+            challenge_id: Set(test.challenge_id),
+            creation_timestamp: Set(test.creation_timestamp),
+            input: Set(test.input),
+            output: Set(test.output),
+            data: Set(test.data),
+        }
+        .insert(&***db)
+        .await?;
+        CreateTestData::ok(test.into())
+    }
+
+    /// Update a fixed test case of a coding challenge.
+    ///
+    /// The sample solution of the coding challenge has to pass the updated
+    /// test case.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/test_data/:test_id",
+        method = "patch"
+    )]
+    async fn update_test_data(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        test_id: Path<Uuid>,
+        data: Json<UpdateTestDataRequest>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> UpdateTestData::Response<VerifiedUserAuth> {
+        let cc = match query_subtask_admin::<challenges_coding_challenges::Entity, _>(
+            &db,
+            &auth.0,
+            task_id.0,
+            subtask_id.0,
+            |cc, _| cc,
+        )
+        .await?
+        {
+            Ok(cc) => cc,
+            Err(QuerySubtaskAdminError::NotFound) => return UpdateTestData::subtask_not_found(),
+            Err(QuerySubtaskAdminError::NoAccess) => return UpdateTestData::forbidden(),
+        };
+        let Some(test) = get_test(&***db, cc.subtask_id, test_id.0).await? else {
+            return UpdateTestData::test_not_found();
+        };
+
+        let updated = challenges_coding_challenge_test_data::Model {
+            input: data.0.input.get_new(&test.input).clone(),
+            output: data.0.output.get_new(&test.output).clone(),
+            data: data.0.data.get_new(&test.data).clone(),
+            ..test.clone()
+        };
+        if let Err(result) = self.check_test_data(&cc, &updated).await? {
+            return Ok(_CheckError::Response::from(result).into());
+        }
+
+        let test = challenges_coding_challenge_test_data::ActiveModel {
+            id: Unchanged(test.id),
+            challenge_id: Unchanged(test.challenge_id),
+            creation_timestamp: Unchanged(test.creation_timestamp),
+            input: data.0.input.update(test.input),
+            output: data.0.output.update(test.output),
+            data: data.0.data.update(test.data),
+        }
+        .update(&***db)
+        .await?;
+        UpdateTestData::ok(test.into())
+    }
+
+    /// Delete a fixed test case of a coding challenge.
+    #[oai(
+        path = "/tasks/:task_id/coding_challenges/:subtask_id/test_data/:test_id",
+        method = "delete"
+    )]
+    async fn delete_test_data(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        test_id: Path<Uuid>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> DeleteTestData::Response<VerifiedUserAuth> {
+        match query_subtask_admin::<challenges_coding_challenges::Entity, _>(
+            &db,
+            &auth.0,
+            task_id.0,
+            subtask_id.0,
+            |cc, _| cc,
+        )
+        .await?
+        {
+            Ok(_) => {}
+            Err(QuerySubtaskAdminError::NotFound) => return DeleteTestData::subtask_not_found(),
+            Err(QuerySubtaskAdminError::NoAccess) => return DeleteTestData::forbidden(),
+        };
+        let Some(test) = get_test(&***db, subtask_id.0, test_id.0).await? else {
+            return DeleteTestData::test_not_found();
+        };
+
+        test.delete(&***db).await?;
+        DeleteTestData::ok()
+    }
+}
+
+response!(ListTestData = {
+    Ok(200) => Vec<TestData>,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// The user is not allowed to access the test data of this coding challenge.
+    Forbidden(403, error),
+});
+
+response!(CreateTestData = {
+    Ok(201) => TestData,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// The user is not allowed to modify the test data of this coding challenge.
+    Forbidden(403, error),
+    .._CheckError::Response,
+});
+
+response!(UpdateTestData = {
+    Ok(200) => TestData,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// Test case does not exist.
+    TestNotFound(404, error),
+    /// The user is not allowed to modify the test data of this coding challenge.
+    Forbidden(403, error),
+    .._CheckError::Response,
+});
+
+response!(DeleteTestData = {
+    Ok(200),
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+    /// Test case does not exist.
+    TestNotFound(404, error),
+    /// The user is not allowed to modify the test data of this coding challenge.
+    Forbidden(403, error),
+});
+
+impl Api {
+    /// Run the sample solution of a coding challenge on a fixed test case.
+    async fn check_test_data(
+        &self,
+        cc: &challenges_coding_challenges::Model,
+        test: &challenges_coding_challenge_test_data::Model,
+    ) -> Result<Result<(), CheckError>, JudgeError> {
+        let judge = Judge::new(
+            &self.sandkasten,
+            &self.judge_cache,
+            &self.config.challenges.coding_challenges,
+            cc,
+        );
+        let result = match judge
+            .run_test_data(
+                test,
+                &cc.solution_environment,
+                &cc.solution_code,
+                &[],
+                Some(cc.time_limit as _),
+                Some(cc.memory_limit as _),
+            )
+            .await
+        {
+            Err(JudgeError::EnvironmentNotFound) => {
+                return Ok(Err(CheckError::EnvironmentNotFound));
+            }
+            Err(JudgeError::EvaluatorFailed(err)) => {
+                return Ok(Err(CheckError::EvaluatorFailed(err)));
+            }
+            Err(JudgeError::InvalidOutput(err)) => {
+                return Ok(Err(CheckError::InvalidOutput(err)));
+            }
+            x => x?,
+        };
+
+        Ok(match result.verdict {
+            ChallengesVerdict::Ok => Ok(()),
+            _ => Err(CheckError::TestcaseFailed(CheckTestcaseError {
+                seed: test_data_seed(test.id),
+                result,
+            })),
+        })
+    }
+}
+
+/// Return the fixed test cases of a coding challenge in the order in which
+/// they are run.
+pub(super) async fn get_test_data(
+    db: &impl ConnectionTrait,
+    challenge_id: Uuid,
+) -> Result<Vec<challenges_coding_challenge_test_data::Model>, DbErr> {
+    challenges_coding_challenge_test_data::Entity::find()
+        .filter(challenges_coding_challenge_test_data::Column::ChallengeId.eq(challenge_id))
+        .order_by_asc(challenges_coding_challenge_test_data::Column::CreationTimestamp)
+        .order_by_asc(challenges_coding_challenge_test_data::Column::Id)
+        .all(db)
+        .await
+}
+
+async fn get_test(
+    db: &impl ConnectionTrait,
+    challenge_id: Uuid,
+    test_id: Uuid,
+) -> Result<Option<challenges_coding_challenge_test_data::Model>, DbErr> {
+    challenges_coding_challenge_test_data::Entity::find_by_id(test_id)
+        .filter(challenges_coding_challenge_test_data::Column::ChallengeId.eq(challenge_id))
+        .one(db)
+        .await
+}
@@ -1,5 +1,6 @@
 use std::{sync::Arc, time::Duration};
 
+use entity::challenges_coding_challenges;
 use fnct::{format::JsonFormatter, key};
 use lib::{auth::VerifiedUserAuth, Cache, SharedState};
 use poem::web::Data;
@@ -8,15 +9,19 @@ use poem_openapi::{
     param::{Path, Query},
     OpenApi,
 };
-use schemas::challenges::leaderboard::{Leaderboard, Rank};
+use schemas::challenges::leaderboard::{Leaderboard, PerformanceMetric, Rank};
 use uuid::Uuid;
 use chrono::NaiveDateTime;
 
 use super::Tags;
-use crate::services::leaderboard::{
-    global::{get_global_leaderboard, get_global_leaderboard_user},
-    language::{get_language_leaderboard, get_language_leaderboard_user},
-    task::{get_task_leaderboard, get_task_leaderboard_user},
+use crate::services::{
+    leaderboard::{
+        global::{get_global_leaderboard, get_global_leaderboard_user},
+        language::{get_language_leaderboard, get_language_leaderboard_user},
+        performance::get_performance_leaderboard,
+        task::{get_task_leaderboard, get_task_leaderboard_user},
+    },
+    subtasks::get_subtask,
 };
 
 pub struct LeaderboardEndpoints {
@@ -209,6 +214,58 @@ impl LeaderboardEndpoints {
             .await??;
         GetLanguageLeaderboardUser::ok(rank)
     }
+
+    /// Rank the users who solved a coding challenge by the resource usage of
+    /// their best accepted submission.
+    #[oai(
+        path = "/leaderboard/by-task/:task_id/challenges/:subtask_id",
+        method = "get"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    async fn get_performance_leaderboard(
+        &self,
+        task_id: Path<Uuid>,
+        subtask_id: Path<Uuid>,
+        /// The resource usage by which users are ranked.
+        metric: Query<PerformanceMetric>,
+        /// Only consider submissions written for this environment.
+        environment: Query<Option<String>>,
+        #[oai(validator(maximum(value = "100")))] limit: Query<u64>,
+        offset: Query<u64>,
+        db: Data<&DbTxn>,
+        auth: VerifiedUserAuth,
+    ) -> GetPerformanceLeaderboard::Response<VerifiedUserAuth> {
+        let Some((_, subtask)) =
+            get_subtask::<challenges_coding_challenges::Entity>(&db, task_id.0, subtask_id.0)
+                .await?
+        else {
+            return GetPerformanceLeaderboard::subtask_not_found();
+        };
+        if !auth.0.admin && auth.0.id != subtask.creator && !subtask.enabled {
+            return GetPerformanceLeaderboard::subtask_not_found();
+        }
+
+        let leaderboard = self
+            .cache
+            .cached_result(
+                key!(subtask.id, metric.0, &environment.0, limit.0, offset.0),
+                &[],
+                Some(Duration::from_secs(10)),
+                || {
+                    get_performance_leaderboard(
+                        &db,
+                        &self.state.services,
+                        &subtask,
+                        metric.0,
+                        environment.0.as_deref(),
+                        limit.0,
+                        offset.0,
+                    )
+                },
+            )
+            .await??;
+        GetPerformanceLeaderboard::ok(leaderboard)
+    }
 }
 
 response!(GetLeaderboard = {
@@ -234,3 +291,9 @@ response!(GetLanguageLeaderboard = {
 response!(GetLanguageLeaderboardUser = {
     Ok(200) => Rank,
 });
+
+response!(GetPerformanceLeaderboard = {
+    Ok(200) => Leaderboard,
+    /// Subtask does not exist.
+    SubtaskNotFound(404, error),
+});
@@ -4,13 +4,13 @@ use fnct::format::JsonFormatter;
 use lib::{config::Config, SharedState};
 use poem_openapi::OpenApi;
 use sandkasten_client::SandkastenClient;
-use tokio::sync::Semaphore;
 
 use self::{
     challenges::Challenges, coding_challenges::CodingChallenges, course_tasks::CourseTasks,
     leaderboard::LeaderboardEndpoints, matchings::Matchings, multiple_choice::MultipleChoice,
     question::Questions, subtasks::Subtasks,
 };
+use crate::services::{rate_limit::RateLimit, submission_events::SubmissionEvents};
 
 mod challenges;
 pub mod coding_challenges;
@@ -45,6 +45,8 @@ pub async fn setup_api(
     state: Arc<SharedState>,
     config: Arc<Config>,
     sandkasten: SandkastenClient,
+    submission_events: SubmissionEvents,
+    rate_limit: RateLimit,
 ) -> anyhow::Result<impl OpenApi> {
     Ok((
         Challenges {
@@ -75,9 +77,8 @@ pub async fn setup_api(
             judge_cache: state.cache.with_formatter(JsonFormatter),
             state: Arc::clone(&state),
             sandkasten,
-            judge_lock: Arc::new(Semaphore::new(
-                config.challenges.coding_challenges.max_concurrency,
-            )),
+            submission_events,
+            rate_limit,
             config,
         }
         .setup_api()
@@ -1,8 +1,8 @@
 use std::sync::Arc;
 
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Utc};
 use entity::{
-    challenges_ban, challenges_subtask_reports, challenges_subtasks, challenges_user_subtasks,
+    challenges_subtask_reports, challenges_subtasks, challenges_user_subtasks,
     sea_orm_active_enums::{ChallengesBanAction, ChallengesReportReason},
 };
 use lib::{
@@ -20,8 +20,7 @@ use schemas::challenges::subtasks::{
     CreateReportRequest, Report, ResolveReportAction, ResolveReportRequest,
 };
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseTransaction, EntityTrait, ModelTrait, PaginatorTrait,
-    QueryFilter, QueryOrder, QuerySelect, Set,
+    ActiveModelTrait, DatabaseTransaction, EntityTrait, ModelTrait, QueryOrder, QuerySelect, Set,
 };
 use uuid::Uuid;
 
@@ -29,7 +28,7 @@ use super::get_subtask;
 use crate::{
     endpoints::Tags,
     services::subtasks::{
-        get_active_ban, get_user_subtask, update_user_subtask, ActiveBan, UserSubtaskExt,
+        ban_user, get_active_ban, get_user_subtask, update_user_subtask, ActiveBan, UserSubtaskExt,
     },
 };
 
@@ -236,36 +235,3 @@ pub(super) async fn create_report(
 
     Ok((Report::from(report, &subtask), subtask))
 }
-
-async fn ban_user(
-    db: &DatabaseTransaction,
-    user_id: Uuid,
-    action: ChallengesBanAction,
-    ban_days: &[u32],
-    creator: Uuid,
-    reason: String,
-) -> Result<challenges_ban::Model, ErrorResponse> {
-    let now = Utc::now().naive_utc();
-
-    let bans = challenges_ban::Entity::find()
-        .filter(challenges_ban::Column::UserId.eq(user_id))
-        .filter(challenges_ban::Column::Action.eq(action))
-        .count(db)
-        .await?;
-
-    let duration = ban_days
-        .get(bans as usize)
-        .map(|&days| Duration::days(days as _));
-
-    Ok(challenges_ban::ActiveModel {
-        id: Set(Uuid::new_v4()),
-        user_id: Set(user_id),
-        start: Set(now),
-        end: Set(duration.map(|duration| now + duration)),
-        action: Set(action),
-        creator: Set(creator),
-        reason: Set(reason),
-    }
-    .insert(db)
-    .await?)
-}
@@ -4,8 +4,18 @@
 use std::{sync::Arc, time::Duration};
 
 use fnct::{backend::AsyncRedisBackend, format::PostcardFormatter};
-use lib::{config, jwt::JwtSecret, redis::RedisConnection, services::Services, Cache, SharedState};
-use poem::{listener::TcpListener, middleware::Tracing, EndpointExt, Route, Server};
+use lib::{
+    config, jwt::JwtSecret, metrics::track_http_requests, redis::RedisConnection,
+    services::Services, Cache, SharedState,
+};
+use poem::{
+    get, handler,
+    http::StatusCode,
+    listener::TcpListener,
+    middleware::Tracing,
+    web::{Data, WithContentType},
+    EndpointExt, IntoResponse, Route, Server,
+};
 use poem_ext::{db::DbTransactionMiddleware, panic_handler::PanicHandler};
 use poem_openapi::OpenApiService;
 use sandkasten_client::SandkastenClient;
@@ -14,7 +24,10 @@ use sentry::integrations::tracing::EventFilter;
 use tracing::{info, warn, Level};
 use tracing_subscriber::{prelude::*, EnvFilter};
 
-use crate::endpoints::setup_api;
+use crate::{
+    endpoints::{coding_challenges::submissions::lease_duration, setup_api},
+    services::{metrics, rate_limit::RateLimit, submission_events::SubmissionEvents},
+};
 
 mod endpoints;
 mod services;
@@ -61,6 +74,8 @@ async fn main() -> anyhow::Result<()> {
         Duration::from_secs(config.cache_ttl),
     );
     let auth_redis = RedisConnection::new(config.redis.auth.as_str()).await?;
+    let submission_events = SubmissionEvents::new(config.redis.challenges.as_str()).await?;
+    let rate_limit = RateLimit::new(config.redis.challenges.as_str()).await?;
 
     info!("Connecting to Sandkasten");
     let sandkasten =
@@ -91,7 +106,14 @@ async fn main() -> anyhow::Result<()> {
     });
 
     let api_service = OpenApiService::new(
-        setup_api(shared_state.clone(), Arc::clone(&config), sandkasten).await?,
+        setup_api(
+            shared_state.clone(),
+            Arc::clone(&config),
+            sandkasten,
+            submission_events,
+            rate_limit,
+        )
+        .await?,
         "Bootstrap Academy Backend: Challenges Microservice",
         env!("CARGO_PKG_VERSION"),
     )
@@ -104,19 +126,50 @@ async fn main() -> anyhow::Result<()> {
         .nest("/", api_service)
         .with(Tracing)
         .with(PanicHandler::middleware())
+        .around(track_http_requests)
         .with(DbTransactionMiddleware::new(db))
+        .data(shared_state.clone());
+
+    // metrics are served on a separate internal listener, so that they are
+    // not publicly accessible
+    let metrics_app = Route::new()
+        .at("/metrics", get(get_metrics))
+        .with(Tracing)
+        .with(PanicHandler::middleware())
+        .data(Arc::clone(&config))
         .data(shared_state);
+    let metrics_server = async {
+        let Some(metrics) = &config.challenges.metrics else {
+            return Ok(());
+        };
+        info!("Serving metrics on {}:{}", metrics.host, metrics.port);
+        Server::new(TcpListener::bind((metrics.host.as_str(), metrics.port)))
+            .run(metrics_app)
+            .await
+    };
 
     info!(
         "Listening on {}:{}",
         config.challenges.host, config.challenges.port
     );
-    Server::new(TcpListener::bind((
+    let server = Server::new(TcpListener::bind((
         config.challenges.host.as_str(),
         config.challenges.port,
     )))
-    .run(app)
-    .await?;
+    .run(app);
+    tokio::try_join!(server, metrics_server)?;
 
     Ok(())
 }
+
+/// Return the metrics of this microservice in the Prometheus text format.
+#[handler]
+async fn get_metrics(
+    state: Data<&Arc<SharedState>>,
+    config: Data<&Arc<config::Config>>,
+) -> poem::Result<WithContentType<String>> {
+    let metrics = metrics::render(&state.db, lease_duration(&config))
+        .await
+        .map_err(|err| poem::Error::new(err, StatusCode::INTERNAL_SERVER_ERROR))?;
+    Ok(metrics.with_content_type("text/plain; version=0.0.4"))
+}
@@ -1,28 +1,138 @@
-use entity::sea_orm_active_enums::ChallengesVerdict;
+use std::time::Duration;
+
+use entity::{
+    challenges_coding_challenge_test_data, challenges_coding_challenges,
+    sea_orm_active_enums::{ChallengesChecker, ChallengesVerdict},
+};
 use fnct::{format::JsonFormatter, key};
-use lib::{Cache, CacheError};
+use lib::{config::challenges::CodingChallenges, Cache, CacheError};
 use sandkasten_client::{
     schemas::{
         programs::{
-            BuildRequest, BuildRunError, BuildRunRequest, BuildRunResult, File, LimitsOpt,
-            MainFile, RunRequest, RunResult,
+            BuildError, BuildRequest, BuildResult, BuildRunResult, File, LimitsOpt, MainFile,
+            RunError, RunRequest, RunResult,
         },
-        ErrorResponse,
+        ErrorResponse, GeneralError,
     },
     Error as SandkastenError, SandkastenClient,
 };
-use schemas::challenges::coding_challenges::{CheckResult, Example, ExecutorConfig};
+use schemas::challenges::coding_challenges::{CheckResult, Example, ExecutorConfig, RunCodeResult};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::Value;
+use sha2::{Digest, Sha256};
 use thiserror::Error;
+use uuid::Uuid;
+
+use super::metrics::SANDKASTEN_ERRORS;
+
+/// The library and template for evaluators written for a specific
+/// Sandkasten environment.
+#[derive(Debug)]
+pub struct EvaluatorAssets {
+    pub environment: &'static str,
+    /// The name of the file the library is made available as.
+    pub library_name: &'static str,
+    pub library: &'static str,
+    pub template: &'static str,
+}
+
+pub const EVALUATOR_ASSETS: &[EvaluatorAssets] = &[
+    EvaluatorAssets {
+        environment: "python",
+        library_name: "lib.py",
+        library: include_str!("../../assets/evaluator/lib.py"),
+        template: include_str!("../../assets/evaluator/template.py"),
+    },
+    EvaluatorAssets {
+        environment: "javascript",
+        library_name: "lib.js",
+        library: include_str!("../../assets/evaluator/lib.js"),
+        template: include_str!("../../assets/evaluator/template.js"),
+    },
+];
 
-pub const EVALUATOR_TEMPLATE: &str = include_str!("../../assets/evaluator/template.py");
-pub const EVALUATOR_LIBRARY: &str = include_str!("../../assets/evaluator/lib.py");
+/// Return the evaluator library and template for the given environment.
+pub fn get_evaluator_assets(environment: &str) -> Option<&'static EvaluatorAssets> {
+    EVALUATOR_ASSETS
+        .iter()
+        .find(|assets| assets.environment == environment)
+}
 
+#[derive(Clone, Copy)]
 pub struct Judge<'a> {
     pub sandkasten: &'a SandkastenClient,
+    /// The id of the evaluator revision, used to key cached results.
+    pub evaluator_id: Uuid,
+    /// The environment the evaluator is written for.
+    pub evaluator_environment: &'a str,
     pub evaluator: &'a str,
+    pub checker: Checker<'a>,
     pub cache: &'a Cache<JsonFormatter>,
+    /// Used to determine the limits of solutions for each environment.
+    pub config: &'a CodingChallenges,
+}
+
+/// Determines how the output of a solution is checked.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Checker<'a> {
+    pub mode: ChallengesChecker,
+    /// The environment of the sample solution.
+    pub reference_environment: &'a str,
+    /// The sample solution, which is used by the built-in checkers to
+    /// determine the expected output if the evaluator does not provide it.
+    pub reference_code: &'a str,
+}
+
+impl<'a> From<&'a challenges_coding_challenges::Model> for Checker<'a> {
+    fn from(cc: &'a challenges_coding_challenges::Model) -> Self {
+        Self {
+            mode: cc.checker,
+            reference_environment: &cc.solution_environment,
+            reference_code: &cc.solution_code,
+        }
+    }
+}
+
+impl<'a> Judge<'a> {
+    /// Create a judge for the current evaluator of a coding challenge.
+    pub fn new(
+        sandkasten: &'a SandkastenClient,
+        cache: &'a Cache<JsonFormatter>,
+        config: &'a CodingChallenges,
+        cc: &'a challenges_coding_challenges::Model,
+    ) -> Self {
+        Self::with_evaluator(
+            sandkasten,
+            cache,
+            config,
+            cc.evaluator_id,
+            &cc.evaluator_environment,
+            &cc.evaluator,
+            Checker::from(cc),
+        )
+    }
+
+    /// Create a judge for an evaluator that is not (yet) the current
+    /// evaluator of a coding challenge.
+    pub fn with_evaluator(
+        sandkasten: &'a SandkastenClient,
+        cache: &'a Cache<JsonFormatter>,
+        config: &'a CodingChallenges,
+        evaluator_id: Uuid,
+        evaluator_environment: &'a str,
+        evaluator: &'a str,
+        checker: Checker<'a>,
+    ) -> Self {
+        Self {
+            sandkasten,
+            evaluator_id,
+            evaluator_environment,
+            evaluator,
+            checker,
+            cache,
+            config,
+        }
+    }
 }
 
 impl Judge<'_> {
@@ -31,16 +141,55 @@ impl Judge<'_> {
         seed: &str,
         solution_environment: &str,
         solution_code: &str,
+        solution_files: &[File],
         time_limit: Option<u64>,
         memory_limit: Option<u64>,
     ) -> Result<Result<Example, CheckResult<RunResult>>, Error> {
+        let input = self.generate(seed).await?;
+        let result = self
+            .run_test(
+                seed,
+                solution_environment,
+                solution_code,
+                solution_files,
+                time_limit,
+                memory_limit,
+            )
+            .await?;
+        Ok(match result {
+            CheckResult {
+                verdict: ChallengesVerdict::Ok,
+                run: Some(run),
+                ..
+            } => Ok(Example {
+                id: seed.into(),
+                input: input.input,
+                output: run.stdout,
+                explanation: (!run.stderr.is_empty()).then_some(run.stderr),
+            }),
+            _ => Err(result),
+        })
+    }
+
+    pub async fn run_test(
+        &self,
+        seed: &str,
+        solution_environment: &str,
+        solution_code: &str,
+        solution_files: &[File],
+        time_limit: Option<u64>,
+        memory_limit: Option<u64>,
+    ) -> Result<CheckResult<RunResult>, Error> {
         self.cache
             .cached_result(
                 key!(
-                    self.evaluator,
+                    self.evaluator_id,
+                    self.checker,
                     seed,
                     solution_environment,
                     solution_code,
+                    solution_files,
+                    self.config.limit_multipliers(solution_environment),
                     time_limit,
                     memory_limit
                 ),
@@ -48,37 +197,94 @@ impl Judge<'_> {
                 None,
                 || async {
                     let input = self.generate(seed).await?;
-                    let result = self
-                        .run_solution(
-                            seed,
-                            &input,
-                            solution_environment,
-                            solution_code,
-                            time_limit,
-                            memory_limit,
-                        )
-                        .await?;
-                    Ok(match result {
-                        CheckResult {
-                            verdict: ChallengesVerdict::Ok,
-                            run: Some(run),
-                            ..
-                        } => Ok(Example {
-                            id: seed.into(),
-                            input: input.input,
-                            output: run.stdout,
-                            explanation: (!run.stderr.is_empty()).then_some(run.stderr),
-                        }),
-                        _ => Err(result),
-                    })
+                    self.run_solution(
+                        seed,
+                        &input,
+                        solution_environment,
+                        solution_code,
+                        solution_files,
+                        time_limit,
+                        memory_limit,
+                    )
+                    .await
+                },
+            )
+            .await?
+    }
+
+    /// Run a solution on a fixed test case of a coding challenge.
+    pub async fn run_test_data(
+        &self,
+        test: &challenges_coding_challenge_test_data::Model,
+        solution_environment: &str,
+        solution_code: &str,
+        solution_files: &[File],
+        time_limit: Option<u64>,
+        memory_limit: Option<u64>,
+    ) -> Result<CheckResult<RunResult>, Error> {
+        self.cache
+            .cached_result(
+                key!(
+                    self.evaluator_id,
+                    self.checker,
+                    (test.id, &test.input, &test.output, &test.data),
+                    solution_environment,
+                    solution_code,
+                    solution_files,
+                    self.config.limit_multipliers(solution_environment),
+                    time_limit,
+                    memory_limit
+                ),
+                &[],
+                None,
+                || async {
+                    let input = Input {
+                        input: test.input.clone(),
+                        data: test.data.clone().unwrap_or_default(),
+                        weight: None,
+                        output: None,
+                        expected_output: test.output.clone(),
+                    };
+                    self.run_solution(
+                        &test_data_seed(test.id),
+                        &input,
+                        solution_environment,
+                        solution_code,
+                        solution_files,
+                        time_limit,
+                        memory_limit,
+                    )
+                    .await
                 },
             )
             .await?
     }
 
+    /// Apply the limit multipliers of the environment to the limits of a
+    /// coding challenge, without exceeding the maximum limits of the
+    /// executor.
+    pub async fn effective_limits(
+        &self,
+        environment: &str,
+        time_limit: Option<u64>,
+        memory_limit: Option<u64>,
+    ) -> Result<(Option<u64>, Option<u64>), Error> {
+        if time_limit.is_none() && memory_limit.is_none() {
+            return Ok((None, None));
+        }
+        let multipliers = self.config.limit_multipliers(environment);
+        let max = get_executor_config(self.cache, self.sandkasten)
+            .await
+            .map_err(Error::ExecutorConfig)?;
+        Ok((
+            time_limit.map(|x| multipliers.time_limit(x).min(max.time_limit)),
+            memory_limit.map(|x| multipliers.memory_limit(x).min(max.memory_limit)),
+        ))
+    }
+
     pub async fn examples(&self) -> Result<Vec<String>, Error> {
         self.cache
-            .cached_result(key!(self.evaluator), &[], None, || async {
+            .cached_result(key!(self.evaluator_id), &[], None, || async {
                 self.run_evaluator(vec!["examples".into()], None::<()>)
                     .await
             })
@@ -87,13 +293,39 @@ impl Judge<'_> {
 
     pub async fn generate(&self, seed: &str) -> Result<Input, Error> {
         self.cache
-            .cached_result(key!(self.evaluator, seed), &[], None, || async {
+            .cached_result(key!(self.evaluator_id, seed), &[], None, || async {
                 self.run_evaluator(vec!["generate".into(), seed.into()], None::<()>)
                     .await
             })
             .await?
     }
 
+    /// Run starter code through the `prepare` step of the evaluator for the
+    /// first example. Return the reason if the evaluator rejects the code.
+    pub async fn check_starter_code(
+        &self,
+        environment: &str,
+        code: &str,
+    ) -> Result<Option<String>, Error> {
+        let examples = self.examples().await?;
+        let Some(seed) = examples.first() else {
+            return Ok(None);
+        };
+        let input = self.generate(seed).await?;
+        let result = self
+            .prepare(
+                seed,
+                &PrepareRequest {
+                    environment,
+                    code,
+                    files: &[],
+                    data: &input.data,
+                },
+            )
+            .await?;
+        Ok(result.code.is_none().then_some(result.reason))
+    }
+
     async fn prepare(&self, seed: &str, data: &PrepareRequest<'_>) -> Result<PrepareResult, Error> {
         self.run_evaluator(vec!["prepare".into(), seed.into()], Some(data))
             .await
@@ -109,49 +341,98 @@ impl Judge<'_> {
         args: Vec<String>,
         stdin: Option<I>,
     ) -> Result<O, Error> {
-        let out = self
-            .sandkasten
-            .build_and_run(&BuildRunRequest {
-                build: BuildRequest {
-                    environment: "python".into(),
-                    main_file: MainFile {
-                        content: self.evaluator.to_owned(),
-                        ..Default::default()
-                    },
-                    files: vec![File {
-                        name: "lib.py".into(),
-                        content: EVALUATOR_LIBRARY.into(),
-                    }],
-                    ..Default::default()
-                },
-                run: RunRequest {
-                    args,
-                    stdin: stdin.map(|s| serde_json::to_string(&s)).transpose()?,
-                    ..Default::default()
-                },
-            })
-            .await?;
+        let stdin = stdin.map(|s| serde_json::to_string(&s)).transpose()?;
+        let out = self.exec_evaluator(args, stdin).await?;
         if out.run.status != 0 {
             return Err(Error::EvaluatorFailed(out));
         }
         serde_json::from_str(&out.run.stdout).map_err(|_| Error::InvalidOutput(out))
     }
 
+    /// Run a command of the evaluator and return its raw result without
+    /// interpreting its output, e.g. to debug a draft evaluator.
+    pub async fn debug_evaluator(
+        &self,
+        command: EvaluatorCommand<'_>,
+    ) -> Result<BuildRunResult, Error> {
+        let (args, stdin) = match command {
+            EvaluatorCommand::Examples => (vec!["examples".into()], None),
+            EvaluatorCommand::Generate { seed } => (vec!["generate".into(), seed.into()], None),
+            EvaluatorCommand::Prepare {
+                seed,
+                environment,
+                code,
+                files,
+                data,
+            } => (
+                vec!["prepare".into(), seed.into()],
+                Some(serde_json::to_string(&PrepareRequest {
+                    environment,
+                    code,
+                    files,
+                    data,
+                })?),
+            ),
+            EvaluatorCommand::Check { seed, output, data } => (
+                vec!["check".into(), seed.into()],
+                Some(serde_json::to_string(&Output { output, data })?),
+            ),
+        };
+        self.exec_evaluator(args, stdin).await
+    }
+
+    async fn exec_evaluator(
+        &self,
+        args: Vec<String>,
+        stdin: Option<String>,
+    ) -> Result<BuildRunResult, Error> {
+        self.build_and_run(
+            &BuildRequest {
+                environment: self.evaluator_environment.into(),
+                main_file: MainFile {
+                    content: self.evaluator.to_owned(),
+                    ..Default::default()
+                },
+                files: get_evaluator_assets(self.evaluator_environment)
+                    .map(|assets| {
+                        vec![File {
+                            name: assets.library_name.into(),
+                            content: assets.library.into(),
+                        }]
+                    })
+                    .unwrap_or_default(),
+                ..Default::default()
+            },
+            &RunRequest {
+                args,
+                stdin,
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub async fn run_solution(
         &self,
         seed: &str,
         input: &Input,
         environment: &str,
         code: &str,
+        files: &[File],
         time_limit: Option<u64>,   // ms
         memory_limit: Option<u64>, // mb
     ) -> Result<CheckResult<RunResult>, Error> {
+        let (time_limit, memory_limit) = self
+            .effective_limits(environment, time_limit, memory_limit)
+            .await?;
         let prepare_result = self
             .prepare(
                 seed,
                 &PrepareRequest {
                     environment,
                     code,
+                    files,
                     data: &input.data,
                 },
             )
@@ -169,17 +450,17 @@ impl Judge<'_> {
         };
 
         let output = match self
-            .sandkasten
-            .build_and_run(&BuildRunRequest {
-                build: BuildRequest {
+            .build_and_run(
+                &BuildRequest {
                     environment: environment.into(),
                     main_file: MainFile {
                         content: code,
                         ..Default::default()
                     },
+                    files: files.to_vec(),
                     ..Default::default()
                 },
-                run: RunRequest {
+                &RunRequest {
                     stdin: Some(input.input.clone()),
                     run_limits: LimitsOpt {
                         time: time_limit.map(|x| x / 1000 + 1),
@@ -188,23 +469,21 @@ impl Judge<'_> {
                     },
                     ..Default::default()
                 },
-            })
+            )
             .await
         {
-            Err(SandkastenError::ErrorResponse(err)) => {
+            Err(Error::Build(SandkastenError::ErrorResponse(err))) => {
                 return match *err {
-                    ErrorResponse::Inner(BuildRunError::EnvironmentNotFound) => {
+                    ErrorResponse::Inner(BuildError::EnvironmentNotFound) => {
                         Err(Error::EnvironmentNotFound)
                     }
-                    ErrorResponse::Inner(BuildRunError::CompileError(result)) => Ok(CheckResult {
+                    ErrorResponse::Inner(BuildError::CompileError(result)) => Ok(CheckResult {
                         verdict: ChallengesVerdict::CompilationError,
                         reason: None,
                         compile: Some(result),
                         run: None,
                     }),
-                    err => Err(Error::Sandkasten(SandkastenError::ErrorResponse(Box::new(
-                        err,
-                    )))),
+                    err => Err(Error::Build(SandkastenError::ErrorResponse(Box::new(err)))),
                 }
             }
             x => x?,
@@ -227,6 +506,25 @@ impl Judge<'_> {
                 run: Some(output.run),
             });
         }
+        let expected_output = match (self.checker.mode, &input.expected_output, &input.output) {
+            (_, Some(expected_output), _) => Some(expected_output.clone()),
+            (ChallengesChecker::Evaluator, None, _) => None,
+            (_, None, Some(output)) => Some(output.clone()),
+            (_, None, None) => Some(self.reference_output(seed, &input.input).await?),
+        };
+        if let Some(expected_output) = expected_output {
+            let ok = compare_output(self.checker.mode, &output.run.stdout, &expected_output);
+            return Ok(CheckResult {
+                verdict: if ok {
+                    ChallengesVerdict::Ok
+                } else {
+                    ChallengesVerdict::WrongAnswer
+                },
+                reason: (!ok).then(|| "output does not match the expected output".into()),
+                compile: output.build,
+                run: Some(output.run),
+            });
+        }
         let result = self
             .check(
                 seed,
@@ -243,6 +541,243 @@ impl Judge<'_> {
             run: Some(output.run),
         })
     }
+
+    /// Run the sample solution on the given input and return its output.
+    /// Only the outputs for the examples are cached, because random inputs are
+    /// unlikely to be needed again.
+    async fn reference_output(&self, seed: &str, input: &str) -> Result<String, Error> {
+        let Checker {
+            reference_environment,
+            reference_code,
+            ..
+        } = self.checker;
+        let run = || async {
+            let output = self
+                .build_and_run(
+                    &BuildRequest {
+                        environment: reference_environment.into(),
+                        main_file: MainFile {
+                            content: reference_code.into(),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    &RunRequest {
+                        stdin: Some(input.into()),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+            if output.run.status != 0 {
+                return Err(Error::ReferenceFailed(output));
+            }
+            Ok(output.run.stdout)
+        };
+        if !self.examples().await?.iter().any(|example| example == seed) {
+            return run().await;
+        }
+        self.cache
+            .cached_result(
+                key!(reference_environment, reference_code, input),
+                &[],
+                None,
+                run,
+            )
+            .await?
+    }
+
+    /// Run a program on custom input without checking its output. The limits
+    /// are adjusted for the environment in the same way as for submissions.
+    pub async fn run_code(
+        &self,
+        environment: &str,
+        code: &str,
+        files: &[File],
+        stdin: &str,
+        time_limit: Option<u64>,   // ms
+        memory_limit: Option<u64>, // mb
+    ) -> Result<RunCodeResult, Error> {
+        let (time_limit, memory_limit) = self
+            .effective_limits(environment, time_limit, memory_limit)
+            .await?;
+        match self
+            .build_and_run(
+                &BuildRequest {
+                    environment: environment.into(),
+                    main_file: MainFile {
+                        content: code.into(),
+                        ..Default::default()
+                    },
+                    files: files.to_vec(),
+                    ..Default::default()
+                },
+                &RunRequest {
+                    stdin: Some(stdin.into()),
+                    run_limits: LimitsOpt {
+                        time: time_limit.map(|x| x / 1000 + 1),
+                        memory: memory_limit,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            )
+            .await
+        {
+            Ok(output) => Ok(RunCodeResult {
+                compile: output.build,
+                run: Some(output.run),
+            }),
+            Err(Error::Build(SandkastenError::ErrorResponse(err))) => match *err {
+                ErrorResponse::Inner(BuildError::EnvironmentNotFound) => {
+                    Err(Error::EnvironmentNotFound)
+                }
+                ErrorResponse::Inner(BuildError::CompileError(result)) => Ok(RunCodeResult {
+                    compile: Some(result),
+                    run: None,
+                }),
+                err => Err(Error::Build(SandkastenError::ErrorResponse(Box::new(err)))),
+            },
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Build a program and run it. Programs are built only once and reused for
+    /// subsequent runs as long as Sandkasten keeps them around.
+    async fn build_and_run(
+        &self,
+        build: &BuildRequest,
+        run: &RunRequest,
+    ) -> Result<BuildRunResult, Error> {
+        let key = key!(build);
+        let mut rebuilt = false;
+        loop {
+            let program = match self.cache.get::<Program, _>(&key).await? {
+                Some(program) => program,
+                None => {
+                    let result = self.sandkasten.build(build).await;
+                    if result.as_ref().is_err_and(|err| !is_program_error(err)) {
+                        SANDKASTEN_ERRORS.inc(&["build"]);
+                    }
+                    let program = Program::from(result?);
+                    // the cache entry must not outlive the program in Sandkasten
+                    let ttl = Duration::from_secs(program.ttl);
+                    self.cache.put(&key, &program, &[], Some(ttl)).await?;
+                    program
+                }
+            };
+            let result = self.sandkasten.run(program.program_id, run).await;
+            if result.as_ref().is_err_and(|err| {
+                !matches!(err, SandkastenError::ErrorResponse(err)
+                    if matches!(**err, ErrorResponse::Inner(RunError::ProgramNotFound)))
+            }) {
+                SANDKASTEN_ERRORS.inc(&["run"]);
+            }
+            match result {
+                Err(SandkastenError::ErrorResponse(err))
+                    if !rebuilt
+                        && matches!(*err, ErrorResponse::Inner(RunError::ProgramNotFound)) =>
+                {
+                    // the program has expired, so we have to build it again
+                    self.cache.pop_key(&key).await?;
+                    rebuilt = true;
+                }
+                result => {
+                    return Ok(BuildRunResult {
+                        program_id: program.program_id,
+                        ttl: program.ttl,
+                        cached: program.cached,
+                        build: program.compile_result,
+                        run: result?,
+                    })
+                }
+            }
+        }
+    }
+}
+
+/// Return whether a build error is caused by the program instead of Sandkasten.
+fn is_program_error(err: &SandkastenError<BuildError>) -> bool {
+    matches!(err, SandkastenError::ErrorResponse(err) if matches!(
+        **err,
+        ErrorResponse::Inner(BuildError::EnvironmentNotFound | BuildError::CompileError(_))
+    ))
+}
+
+/// Return the seed that identifies a fixed test case in test results.
+pub fn test_data_seed(test_id: Uuid) -> String {
+    format!("_data_{test_id}")
+}
+
+/// Return a hash of everything the deterministic tests of a submission depend
+/// on, i.e. the evaluator revision, the checker, the solution, the effective
+/// limits and the tests that are run (the number of static tests, the fixed
+/// test cases and the number of random tests). The order of the additional
+/// files is irrelevant.
+pub fn submission_hash(
+    evaluator_id: Uuid,
+    checker: Checker<'_>,
+    environment: &str,
+    code: &str,
+    files: &[File],
+    limits: (u64, u64),
+    (static_tests, test_data, random_tests): (
+        u64,
+        &[challenges_coding_challenge_test_data::Model],
+        u64,
+    ),
+) -> String {
+    let mut files = files.iter().collect::<Vec<_>>();
+    files.sort_by_key(|file| &file.name);
+    let test_data = test_data
+        .iter()
+        .map(|test| (test.id, &test.input, &test.output, &test.data))
+        .collect::<Vec<_>>();
+    let tests = (static_tests, test_data, random_tests);
+    let data = serde_json::to_vec(&(
+        evaluator_id,
+        checker,
+        environment,
+        code,
+        files,
+        limits,
+        tests,
+    ))
+    .expect("submission data is always serializable");
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// The maximum absolute or relative error accepted by the `FLOAT` checker.
+const FLOAT_TOLERANCE: f64 = 1e-6;
+
+/// Compare the output of a solution to the expected output of a test case.
+///
+/// Test cases with a fixed expected output are compared token by token if
+/// the coding challenge uses the evaluator to check solutions.
+fn compare_output(checker: ChallengesChecker, output: &str, expected: &str) -> bool {
+    match checker {
+        ChallengesChecker::Exact => output.trim_end() == expected.trim_end(),
+        ChallengesChecker::Tokens | ChallengesChecker::Evaluator => {
+            output.split_whitespace().eq(expected.split_whitespace())
+        }
+        ChallengesChecker::Float => {
+            let mut output = output.split_whitespace();
+            let mut expected = expected.split_whitespace();
+            loop {
+                match (output.next(), expected.next()) {
+                    (None, None) => return true,
+                    (Some(a), Some(b)) if a == b => {}
+                    (Some(a), Some(b)) => match (a.parse::<f64>(), b.parse::<f64>()) {
+                        (Ok(a), Ok(b)) if (a - b).abs() <= FLOAT_TOLERANCE * b.abs().max(1.0) => {}
+                        _ => return false,
+                    },
+                    _ => return false,
+                }
+            }
+        }
+    }
 }
 
 pub async fn get_executor_config(
@@ -261,22 +796,89 @@ pub async fn get_executor_config(
 pub enum Error {
     #[error("cache error: {0}")]
     Cache(#[from] CacheError<JsonFormatter>),
-    #[error("sandkasten error: {0}")]
-    Sandkasten(#[from] sandkasten_client::Error<BuildRunError>),
+    #[error("sandkasten build error: {0}")]
+    Build(#[from] sandkasten_client::Error<BuildError>),
+    #[error("sandkasten run error: {0}")]
+    Run(#[from] sandkasten_client::Error<RunError>),
     #[error("serde_json error: {0}")]
     SerdeJson(#[from] serde_json::Error),
+    #[error("failed to get executor config: {0}")]
+    ExecutorConfig(anyhow::Error),
     #[error("environment does not exist")]
     EnvironmentNotFound,
     #[error("failed to execute evaluator: {0:?}")]
     EvaluatorFailed(BuildRunResult),
     #[error("evaluator failed to produce valid output: {0:?}")]
     InvalidOutput(BuildRunResult),
+    #[error("sample solution failed: {0:?}")]
+    ReferenceFailed(BuildRunResult),
+}
+
+impl Error {
+    /// Return whether the error is caused by a temporary problem, e.g. because
+    /// Sandkasten is unavailable, so that running the same program again may
+    /// succeed.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Cache(_) | Self::ExecutorConfig(_) => true,
+            Self::Build(err) => is_transient(err),
+            Self::Run(err) => is_transient(err),
+            Self::SerdeJson(_)
+            | Self::EnvironmentNotFound
+            | Self::EvaluatorFailed(_)
+            | Self::InvalidOutput(_)
+            | Self::ReferenceFailed(_) => false,
+        }
+    }
+}
+
+/// Return whether a Sandkasten request failed because of a network error or
+/// an unexpected error response instead of a problem with the request.
+fn is_transient<E>(err: &SandkastenError<E>) -> bool {
+    match err {
+        SandkastenError::ReqwestError(_) => true,
+        SandkastenError::ErrorResponse(err) => matches!(
+            **err,
+            ErrorResponse::GeneralError(GeneralError::InternalServerError)
+        ),
+        SandkastenError::UrlParseError(_) => false,
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Program {
+    program_id: Uuid,
+    ttl: u64,
+    cached: bool,
+    compile_result: Option<RunResult>,
+}
+
+impl From<BuildResult> for Program {
+    fn from(value: BuildResult) -> Self {
+        Self {
+            program_id: value.program_id,
+            ttl: value.ttl,
+            cached: value.cached,
+            compile_result: value.compile_result,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Input {
     pub input: String,
     pub data: Value,
+    /// The weight of the test case in the `WEIGHTED` scoring mode.
+    #[serde(default)]
+    pub weight: Option<f64>,
+    /// The expected output generated by the evaluator, which is used by the
+    /// built-in checkers.
+    #[serde(default)]
+    pub output: Option<String>,
+    /// The expected output of a fixed test case. If set, the output of the
+    /// solution is compared to it instead of being checked by the evaluator.
+    #[serde(skip)]
+    pub expected_output: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -285,10 +887,34 @@ pub struct Output<'a> {
     pub data: &'a Value,
 }
 
+/// A command of the evaluator together with its arguments.
+#[derive(Debug, Clone, Copy)]
+pub enum EvaluatorCommand<'a> {
+    /// List the seeds of the examples.
+    Examples,
+    /// Generate the input of a test case.
+    Generate { seed: &'a str },
+    /// Check a solution before it is run and transform its code.
+    Prepare {
+        seed: &'a str,
+        environment: &'a str,
+        code: &'a str,
+        files: &'a [File],
+        data: &'a Value,
+    },
+    /// Check the output of a solution.
+    Check {
+        seed: &'a str,
+        output: &'a str,
+        data: &'a Value,
+    },
+}
+
 #[derive(Debug, Serialize)]
 struct PrepareRequest<'a> {
     environment: &'a str,
     code: &'a str,
+    files: &'a [File],
     data: &'a Value,
 }
 
@@ -303,3 +929,170 @@ struct EvaluatorCheckOutput {
     verdict: ChallengesVerdict,
     reason: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ChallengesChecker::*;
+
+    const CHECKER: Checker = Checker {
+        mode: Tokens,
+        reference_environment: "python",
+        reference_code: "print(input())",
+    };
+
+    const LIMITS: (u64, u64) = (1000, 256);
+    const TESTS: (u64, &[challenges_coding_challenge_test_data::Model], u64) = (3, &[], 2);
+
+    fn file(name: &str, content: &str) -> File {
+        File {
+            name: name.into(),
+            content: content.into(),
+        }
+    }
+
+    #[test]
+    fn evaluator_assets_by_environment() {
+        assert_eq!(
+            get_evaluator_assets("python").unwrap().library_name,
+            "lib.py"
+        );
+        assert_eq!(
+            get_evaluator_assets("javascript").unwrap().library_name,
+            "lib.js"
+        );
+        assert!(get_evaluator_assets("brainfuck").is_none());
+    }
+
+    #[test]
+    fn submission_hash_ignores_file_order() {
+        let hash = |files: &[File]| {
+            submission_hash(
+                Uuid::nil(),
+                CHECKER,
+                "python",
+                "print(1)",
+                files,
+                LIMITS,
+                TESTS,
+            )
+        };
+        let files = [file("a.py", "x = 1"), file("b.py", "y = 2")];
+        let reversed = [file("b.py", "y = 2"), file("a.py", "x = 1")];
+        assert_eq!(hash(&files), hash(&reversed));
+    }
+
+    #[test]
+    fn submission_hash_depends_on_limits_and_code() {
+        let id = Uuid::nil();
+        let hash = submission_hash(id, CHECKER, "python", "print(1)", &[], LIMITS, TESTS);
+        assert_eq!(hash.len(), 64);
+        assert_ne!(
+            hash,
+            submission_hash(id, CHECKER, "python", "print(1)", &[], (2000, 256), TESTS)
+        );
+        assert_ne!(
+            hash,
+            submission_hash(id, CHECKER, "python", "print(2)", &[], LIMITS, TESTS)
+        );
+        assert_ne!(
+            hash,
+            submission_hash(
+                Uuid::max(),
+                CHECKER,
+                "python",
+                "print(1)",
+                &[],
+                LIMITS,
+                TESTS
+            )
+        );
+    }
+
+    #[test]
+    fn submission_hash_depends_on_tests() {
+        let hash = |tests: (u64, &[_], u64)| {
+            submission_hash(
+                Uuid::nil(),
+                CHECKER,
+                "python",
+                "print(1)",
+                &[],
+                LIMITS,
+                tests,
+            )
+        };
+        let test = challenges_coding_challenge_test_data::Model {
+            id: Uuid::nil(),
+            challenge_id: Uuid::nil(),
+            creation_timestamp: Default::default(),
+            input: "1".into(),
+            output: Some("1".into()),
+            data: None,
+        };
+        let updated = challenges_coding_challenge_test_data::Model {
+            output: Some("2".into()),
+            ..test.clone()
+        };
+        let other = challenges_coding_challenge_test_data::Model {
+            id: Uuid::max(),
+            ..test.clone()
+        };
+
+        let test_data = [test];
+
+        let base = hash((3, &test_data, 2));
+        assert_eq!(base, hash((3, &test_data.clone(), 2)));
+        assert_ne!(base, hash(TESTS));
+        assert_ne!(base, hash((4, &test_data, 2)));
+        assert_ne!(base, hash((3, &test_data, 3)));
+        assert_ne!(base, hash((3, &[updated], 2)));
+        assert_ne!(base, hash((3, &[other], 2)));
+    }
+
+    #[test]
+    fn compare_output_ignores_whitespace() {
+        assert!(compare_output(Tokens, "1 2\n3\n", "1 2 3"));
+        assert!(compare_output(Tokens, "  42\r\n", "42"));
+        assert!(compare_output(Tokens, "", "\n"));
+        assert!(compare_output(Evaluator, "1  2", "1 2"));
+    }
+
+    #[test]
+    fn compare_output_mismatch() {
+        assert!(!compare_output(Tokens, "1 2", "1 2 3"));
+        assert!(!compare_output(Tokens, "12", "1 2"));
+        assert!(!compare_output(Tokens, "a", "A"));
+    }
+
+    #[test]
+    fn compare_output_exact() {
+        assert!(compare_output(Exact, "1 2\n3\n", "1 2\n3"));
+        assert!(!compare_output(Exact, "1 2 3", "1 2\n3"));
+        assert!(!compare_output(Exact, " 42", "42"));
+    }
+
+    #[test]
+    fn compare_output_float() {
+        assert!(compare_output(Float, "0.3333333", "0.333333333"));
+        assert!(compare_output(Float, "1e9 2", "1000000000.5 2.0"));
+        assert!(compare_output(Float, "yes 1.5", "yes 1.5000001"));
+        assert!(!compare_output(Float, "0.33", "0.333333333"));
+        assert!(!compare_output(Float, "1.5", "1.5 2"));
+        assert!(!compare_output(Float, "no", "yes"));
+    }
+
+    #[test]
+    fn transient_errors() {
+        fn build_error(err: ErrorResponse<BuildError>) -> Error {
+            Error::Build(SandkastenError::ErrorResponse(Box::new(err)))
+        }
+        assert!(build_error(ErrorResponse::GeneralError(
+            GeneralError::InternalServerError
+        ))
+        .is_transient());
+        assert!(!build_error(ErrorResponse::Inner(BuildError::EnvironmentNotFound)).is_transient());
+        assert!(!Error::EnvironmentNotFound.is_transient());
+    }
+}
@@ -0,0 +1,497 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use entity::{
+    challenges_coding_challenge_judge_jobs, challenges_coding_challenge_judge_workers,
+    challenges_coding_challenge_submissions, sea_orm_active_enums::ChallengesJudgePriority,
+};
+use schemas::challenges::coding_challenges::QueueStatus;
+use sea_orm::{
+    sea_query::{Expr, OnConflict},
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbErr,
+    EntityTrait, PaginatorTrait, QueryFilter, QuerySelect, Set, Statement, TransactionTrait,
+    Unchanged,
+};
+use uuid::Uuid;
+
+/// Add a submission to the judge queue.
+pub async fn enqueue(
+    db: &impl ConnectionTrait,
+    submission: &challenges_coding_challenge_submissions::Model,
+    priority: ChallengesJudgePriority,
+) -> Result<(), DbErr> {
+    challenges_coding_challenge_judge_jobs::ActiveModel {
+        submission_id: Set(submission.id),
+        creation_timestamp: Set(submission.creation_timestamp),
+        worker: Set(None),
+        lease_expiration: Set(None),
+        rejudge: Set(false),
+        priority: Set(priority),
+        attempts: Set(0),
+    }
+    .insert(db)
+    .await?;
+    Ok(())
+}
+
+/// Add existing submissions to the judge queue to judge them again with low
+/// priority. Submissions that are already in the queue are skipped.
+///
+/// Returns the number of submissions that have been added to the queue.
+pub async fn enqueue_rejudge(
+    db: &impl ConnectionTrait,
+    submissions: &[challenges_coding_challenge_submissions::Model],
+) -> Result<u64, DbErr> {
+    if submissions.is_empty() {
+        return Ok(0);
+    }
+    challenges_coding_challenge_judge_jobs::Entity::insert_many(submissions.iter().map(
+        |submission| challenges_coding_challenge_judge_jobs::ActiveModel {
+            submission_id: Set(submission.id),
+            creation_timestamp: Set(Utc::now().naive_utc()),
+            worker: Set(None),
+            lease_expiration: Set(None),
+            rejudge: Set(true),
+            priority: Set(ChallengesJudgePriority::Low),
+            attempts: Set(0),
+        },
+    ))
+    .on_conflict(
+        OnConflict::column(challenges_coding_challenge_judge_jobs::Column::SubmissionId)
+            .do_nothing()
+            .to_owned(),
+    )
+    .exec_without_returning(db)
+    .await
+}
+
+/// Lease the next job according to [`SCHEDULE`] that is not currently being
+/// judged by any worker.
+///
+/// Returns `None` if the queue is empty.
+pub async fn lease(
+    db: &impl TransactionTrait,
+    worker: Uuid,
+    lease_duration: Duration,
+) -> Result<Option<challenges_coding_challenge_judge_jobs::Model>, DbErr> {
+    let txn = db.begin().await?;
+    let now = Utc::now().naive_utc();
+    // jobs that are locked by another worker are skipped
+    let Some(row) = txn
+        .query_one(Statement::from_sql_and_values(
+            txn.get_database_backend(),
+            format!(
+                "{SCHEDULE} SELECT j.submission_id FROM challenges_coding_challenge_judge_jobs j \
+                 JOIN schedule USING (submission_id) \
+                 WHERE j.lease_expiration IS NULL OR j.lease_expiration < $1 \
+                 ORDER BY schedule.position LIMIT 1 FOR UPDATE OF j SKIP LOCKED"
+            ),
+            [now.into()],
+        ))
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let job = challenges_coding_challenge_judge_jobs::ActiveModel {
+        submission_id: Unchanged(row.try_get("", "submission_id")?),
+        worker: Set(Some(worker)),
+        lease_expiration: Set(Some(now + lease_duration)),
+        ..Default::default()
+    }
+    .update(&txn)
+    .await?;
+    txn.commit().await?;
+
+    Ok(Some(job))
+}
+
+/// Refresh the heartbeat of a worker and extend the leases of all jobs it is
+/// currently judging. Workers that have not sent a heartbeat within the lease
+/// duration are removed.
+pub async fn heartbeat(
+    db: &DatabaseConnection,
+    worker: Uuid,
+    concurrency: usize,
+    lease_duration: Duration,
+) -> Result<(), DbErr> {
+    let now = Utc::now().naive_utc();
+    challenges_coding_challenge_judge_workers::Entity::insert(
+        challenges_coding_challenge_judge_workers::ActiveModel {
+            id: Set(worker),
+            concurrency: Set(concurrency as _),
+            heartbeat_timestamp: Set(now),
+        },
+    )
+    .on_conflict(
+        OnConflict::column(challenges_coding_challenge_judge_workers::Column::Id)
+            .update_column(challenges_coding_challenge_judge_workers::Column::HeartbeatTimestamp)
+            .to_owned(),
+    )
+    .exec(db)
+    .await?;
+
+    challenges_coding_challenge_judge_jobs::Entity::update_many()
+        .col_expr(
+            challenges_coding_challenge_judge_jobs::Column::LeaseExpiration,
+            Expr::value(now + lease_duration),
+        )
+        .filter(challenges_coding_challenge_judge_jobs::Column::Worker.eq(worker))
+        .exec(db)
+        .await?;
+
+    challenges_coding_challenge_judge_workers::Entity::delete_many()
+        .filter(
+            challenges_coding_challenge_judge_workers::Column::HeartbeatTimestamp
+                .lt(now - lease_duration),
+        )
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Remove a job from the queue after it has been judged.
+///
+/// Returns `false` if the worker no longer holds the lease of this job.
+pub async fn complete(
+    db: &impl ConnectionTrait,
+    submission_id: Uuid,
+    worker: Uuid,
+) -> Result<bool, DbErr> {
+    let result = challenges_coding_challenge_judge_jobs::Entity::delete_many()
+        .filter(challenges_coding_challenge_judge_jobs::Column::SubmissionId.eq(submission_id))
+        .filter(challenges_coding_challenge_judge_jobs::Column::Worker.eq(worker))
+        .exec(db)
+        .await?;
+    Ok(result.rows_affected > 0)
+}
+
+/// Give up the lease of a job that could not be judged and count the failed
+/// attempt. The job can be leased again after the given backoff.
+pub async fn release(
+    db: &impl ConnectionTrait,
+    submission_id: Uuid,
+    worker: Uuid,
+    backoff: Duration,
+) -> Result<(), DbErr> {
+    challenges_coding_challenge_judge_jobs::Entity::update_many()
+        .col_expr(
+            challenges_coding_challenge_judge_jobs::Column::Worker,
+            Expr::value(None::<Uuid>),
+        )
+        .col_expr(
+            challenges_coding_challenge_judge_jobs::Column::LeaseExpiration,
+            Expr::value(Utc::now().naive_utc() + backoff),
+        )
+        .col_expr(
+            challenges_coding_challenge_judge_jobs::Column::Attempts,
+            Expr::col(challenges_coding_challenge_judge_jobs::Column::Attempts).add(1),
+        )
+        .filter(challenges_coding_challenge_judge_jobs::Column::SubmissionId.eq(submission_id))
+        .filter(challenges_coding_challenge_judge_jobs::Column::Worker.eq(worker))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// Return the queue positions of the given submissions. Submissions that are
+/// currently being judged have position `0`, submissions that are not in the
+/// queue are omitted.
+pub async fn positions(
+    db: &impl ConnectionTrait,
+    submission_ids: impl IntoIterator<Item = Uuid>,
+) -> Result<HashMap<Uuid, usize>, DbErr> {
+    let submission_ids = submission_ids.into_iter().collect::<HashSet<_>>();
+    if submission_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let now = Utc::now().naive_utc();
+    let mut out = challenges_coding_challenge_judge_jobs::Entity::find()
+        .select_only()
+        .column(challenges_coding_challenge_judge_jobs::Column::SubmissionId)
+        .filter(
+            challenges_coding_challenge_judge_jobs::Column::SubmissionId
+                .is_in(submission_ids.iter().copied()),
+        )
+        .filter(is_waiting(now).not())
+        .into_tuple::<Uuid>()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|submission_id| (submission_id, 0))
+        .collect::<HashMap<_, _>>();
+    for row in db
+        .query_all(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            format!(
+                "{SCHEDULE} SELECT submission_id, position FROM schedule \
+                 WHERE submission_id = ANY($2)"
+            ),
+            [
+                now.into(),
+                submission_ids.into_iter().collect::<Vec<_>>().into(),
+            ],
+        ))
+        .await?
+    {
+        let submission_id = row.try_get::<Uuid>("", "submission_id")?;
+        let position = row.try_get::<i64>("", "position")?;
+        out.insert(submission_id, position as _);
+    }
+    Ok(out)
+}
+
+/// Return the queue position of a single submission.
+pub async fn position(
+    db: &impl ConnectionTrait,
+    submission_id: Uuid,
+) -> Result<Option<usize>, DbErr> {
+    Ok(positions(db, [submission_id]).await?.remove(&submission_id))
+}
+
+/// Return the number of submissions of a user that are waiting to be judged
+/// or are currently being judged. Rejudged submissions are not counted.
+pub async fn pending(db: &impl ConnectionTrait, user_id: Uuid) -> Result<u64, DbErr> {
+    challenges_coding_challenge_judge_jobs::Entity::find()
+        .inner_join(challenges_coding_challenge_submissions::Entity)
+        .filter(challenges_coding_challenge_submissions::Column::Creator.eq(user_id))
+        .filter(challenges_coding_challenge_judge_jobs::Column::Rejudge.eq(false))
+        .count(db)
+        .await
+}
+
+/// Return the status of the judge queue across all instances.
+pub async fn status(
+    db: &impl ConnectionTrait,
+    lease_duration: Duration,
+) -> Result<QueueStatus, DbErr> {
+    let now = Utc::now().naive_utc();
+    let workers = challenges_coding_challenge_judge_workers::Entity::find()
+        .filter(
+            challenges_coding_challenge_judge_workers::Column::HeartbeatTimestamp
+                .gte(now - lease_duration),
+        )
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|worker| worker.concurrency as usize)
+        .sum();
+    let waiting = challenges_coding_challenge_judge_jobs::Entity::find()
+        .filter(is_waiting(now))
+        .count(db)
+        .await? as usize;
+    let active = challenges_coding_challenge_judge_jobs::Entity::find()
+        .filter(is_waiting(now).not())
+        .count(db)
+        .await? as usize;
+
+    Ok(QueueStatus {
+        workers,
+        active,
+        waiting,
+    })
+}
+
+/// Common table expressions that rank all jobs that are not currently being
+/// judged in the order in which they are going to be judged. The resulting
+/// `schedule` table contains the `submission_id` and the 1-based `position` of
+/// each of these jobs. `$1` is the current timestamp.
+///
+/// Jobs with a higher priority are always judged first. Within a priority
+/// class, users are served round-robin: The `n`-th waiting job of a user is
+/// judged in round `n + k`, where `k` is the number of jobs of this user that
+/// are currently being judged. Jobs in the same round are judged in the order
+/// in which they have been enqueued.
+const SCHEDULE: &str = "
+    WITH active AS (
+        SELECT s.creator, count(*) AS jobs
+        FROM challenges_coding_challenge_judge_jobs j
+        JOIN challenges_coding_challenge_submissions s ON s.id = j.submission_id
+        WHERE j.worker IS NOT NULL AND j.lease_expiration >= $1
+        GROUP BY s.creator
+    ), waiting AS (
+        SELECT
+            j.submission_id,
+            j.creation_timestamp,
+            j.priority,
+            coalesce(a.jobs, 0) + row_number() OVER (
+                PARTITION BY j.priority, s.creator
+                ORDER BY j.creation_timestamp, j.submission_id
+            ) AS round
+        FROM challenges_coding_challenge_judge_jobs j
+        JOIN challenges_coding_challenge_submissions s ON s.id = j.submission_id
+        LEFT JOIN active a ON a.creator = s.creator
+        WHERE j.worker IS NULL OR j.lease_expiration IS NULL OR j.lease_expiration < $1
+    ), schedule AS (
+        SELECT
+            submission_id,
+            row_number() OVER (
+                ORDER BY priority, round, creation_timestamp, submission_id
+            ) AS position
+        FROM waiting
+    )";
+
+fn is_waiting(now: NaiveDateTime) -> Condition {
+    Condition::any()
+        .add(challenges_coding_challenge_judge_jobs::Column::Worker.is_null())
+        .add(challenges_coding_challenge_judge_jobs::Column::LeaseExpiration.is_null())
+        .add(challenges_coding_challenge_judge_jobs::Column::LeaseExpiration.lt(now))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use sea_orm::{Database, DatabaseTransaction};
+
+    use super::*;
+
+    /// Connect to the test database and start a transaction that is rolled
+    /// back at the end of the test. The queue is empty within the transaction
+    /// and submissions can be created for the coding challenge with the nil
+    /// id.
+    pub(crate) async fn setup() -> DatabaseTransaction {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL is not set");
+        let txn = Database::connect(url).await.unwrap().begin().await.unwrap();
+        txn.execute_unprepared(
+            "DELETE FROM challenges_coding_challenge_judge_jobs;
+             DELETE FROM challenges_coding_challenge_submissions
+                 WHERE subtask_id = '00000000-0000-0000-0000-000000000000';
+             INSERT INTO challenges_tasks (id, creator, creation_timestamp)
+                 VALUES ('00000000-0000-0000-0000-000000000000', gen_random_uuid(), now())
+                 ON CONFLICT DO NOTHING;
+             INSERT INTO challenges_subtasks
+                 (id, task_id, creator, creation_timestamp, xp, coins, ty)
+                 VALUES (
+                     '00000000-0000-0000-0000-000000000000',
+                     '00000000-0000-0000-0000-000000000000',
+                     gen_random_uuid(), now(), 0, 0, 'coding_challenge'
+                 )
+                 ON CONFLICT DO NOTHING;
+             INSERT INTO challenges_coding_challenges
+                 (subtask_id, time_limit, memory_limit, evaluator, description, evaluator_id)
+                 VALUES (
+                     '00000000-0000-0000-0000-000000000000',
+                     1000, 256, '', '', gen_random_uuid()
+                 )
+                 ON CONFLICT DO NOTHING;",
+        )
+        .await
+        .unwrap();
+        txn
+    }
+
+    /// Enqueue submission `id` of user `creator`. Jobs are enqueued in the
+    /// order of their ids.
+    async fn enqueue_job(
+        db: &DatabaseTransaction,
+        id: u128,
+        creator: u128,
+        priority: ChallengesJudgePriority,
+    ) {
+        let submission = challenges_coding_challenge_submissions::ActiveModel {
+            id: Set(Uuid::from_u128(id)),
+            subtask_id: Set(Uuid::nil()),
+            creator: Set(Uuid::from_u128(creator)),
+            creation_timestamp: Set(Utc::now().naive_utc() + Duration::seconds(id as _)),
+            environment: Set("python".into()),
+            code: Set(String::new()),
+            hash: Set(None),
+            hearts: Set(0),
+            shared: Set(false),
+        }
+        .insert(db)
+        .await
+        .unwrap();
+        enqueue(db, &submission, priority).await.unwrap();
+    }
+
+    async fn lease_all(db: &DatabaseTransaction, worker: Uuid) -> Vec<u128> {
+        let mut out = Vec::new();
+        while let Some(job) = lease(db, worker, Duration::minutes(1)).await.unwrap() {
+            assert_eq!(job.worker, Some(worker));
+            out.push(job.submission_id.as_u128());
+        }
+        out
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a migrated PostgreSQL database in DATABASE_URL"]
+    async fn lease_round_robin_between_users() {
+        use ChallengesJudgePriority::Normal;
+        let db = setup().await;
+        for (id, creator) in [(1, 1), (2, 1), (3, 1), (4, 2), (5, 3), (6, 2)] {
+            enqueue_job(&db, id, creator, Normal).await;
+        }
+        assert_eq!(lease_all(&db, Uuid::nil()).await, [1, 4, 5, 2, 6, 3]);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a migrated PostgreSQL database in DATABASE_URL"]
+    async fn lease_prefers_higher_priorities() {
+        use ChallengesJudgePriority::{High, Low, Normal};
+        let db = setup().await;
+        let jobs = [
+            (1, 1, Low),
+            (2, 2, Normal),
+            (3, 2, Normal),
+            (4, 3, High),
+            (5, 1, Normal),
+        ];
+        for (id, creator, priority) in jobs {
+            enqueue_job(&db, id, creator, priority).await;
+        }
+        assert_eq!(lease_all(&db, Uuid::nil()).await, [4, 2, 5, 3, 1]);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a migrated PostgreSQL database in DATABASE_URL"]
+    async fn positions_account_for_active_jobs() {
+        use ChallengesJudgePriority::Normal;
+        let db = setup().await;
+        enqueue_job(&db, 1, 1, Normal).await;
+        lease(&db, Uuid::nil(), Duration::minutes(1)).await.unwrap();
+        for (id, creator) in [(2, 1), (3, 2), (4, 2)] {
+            enqueue_job(&db, id, creator, Normal).await;
+        }
+
+        let positions = positions(&db, (1..=5).map(Uuid::from_u128)).await.unwrap();
+        let expected =
+            [(1, 0), (3, 1), (2, 2), (4, 3)].map(|(id, position)| (Uuid::from_u128(id), position));
+        assert_eq!(positions, HashMap::from(expected));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a migrated PostgreSQL database in DATABASE_URL"]
+    async fn released_jobs_wait_for_backoff() {
+        use ChallengesJudgePriority::Normal;
+        let db = setup().await;
+        let (worker, other) = (Uuid::from_u128(1), Uuid::from_u128(2));
+        enqueue_job(&db, 1, 1, Normal).await;
+        enqueue_job(&db, 2, 2, Normal).await;
+
+        let job = lease(&db, worker, Duration::minutes(1))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(job.submission_id, Uuid::from_u128(1));
+        release(&db, job.submission_id, other, Duration::minutes(1))
+            .await
+            .unwrap();
+        assert_eq!(position(&db, job.submission_id).await.unwrap(), Some(0));
+        release(&db, job.submission_id, worker, Duration::minutes(1))
+            .await
+            .unwrap();
+
+        let job = challenges_coding_challenge_judge_jobs::Entity::find_by_id(job.submission_id)
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(job.worker, None);
+        assert_eq!(job.attempts, 1);
+        assert_eq!(position(&db, job.submission_id).await.unwrap(), Some(1));
+        assert_eq!(lease_all(&db, worker).await, [2]);
+        assert!(!complete(&db, job.submission_id, worker).await.unwrap());
+    }
+}
@@ -2,28 +2,73 @@ use futures::future::try_join_all;
 use lib::services::Services;
 use schemas::challenges::leaderboard::{Leaderboard, LeaderboardUser, Rank};
 use sea_orm::{
-    sea_query::{Alias, BinOper, Expr, Query, SelectStatement},
+    sea_query::{Alias, BinOper, Expr, Query, SelectStatement, SimpleExpr},
     ConnectionTrait, DatabaseTransaction, Order,
 };
 use uuid::Uuid;
 
 pub mod global;
 pub mod language;
+pub mod performance;
 pub mod task;
 
+/// The value by which users are ranked on a leaderboard.
+///
+/// The base query of a leaderboard selects the id of each user, their score
+/// and the timestamp of their last update (used to break ties) in this order.
+struct Score {
+    /// The name of the score column in the base query.
+    column: &'static str,
+    /// The aggregate expression that computes the score in the base query.
+    aggregate: SimpleExpr,
+    /// Whether users with higher (`Desc`) or lower (`Asc`) scores are ranked
+    /// first.
+    order: Order,
+}
+
+impl Score {
+    /// The sum of the xp of all subtasks a user has solved.
+    fn xp() -> Self {
+        Self {
+            column: "xp",
+            aggregate: Expr::col(Alias::new("xp")).sum(),
+            order: Order::Desc,
+        }
+    }
+
+    /// Return whether `a` is ranked lower than `b`.
+    fn is_worse(&self, a: i64, b: i64) -> bool {
+        match self.order {
+            Order::Asc => a > b,
+            _ => a < b,
+        }
+    }
+}
+
 async fn get_leaderboard(
     db: &DatabaseTransaction,
     services: &Services,
     base_query: SelectStatement,
     limit: u64,
     offset: u64,
+) -> anyhow::Result<Leaderboard> {
+    get_ranked_leaderboard(db, services, base_query, &Score::xp(), limit, offset).await
+}
+
+async fn get_ranked_leaderboard(
+    db: &DatabaseTransaction,
+    services: &Services,
+    base_query: SelectStatement,
+    score: &Score,
+    limit: u64,
+    offset: u64,
 ) -> anyhow::Result<Leaderboard> {
     let rows: Vec<(Uuid, i64)> = db
         .query_all(
             db.get_database_backend().build(
                 base_query
                     .clone()
-                    .order_by(Alias::new("xp"), Order::Desc)
+                    .order_by(Alias::new(score.column), score.order.clone())
                     .order_by(Alias::new("last_update"), Order::Asc)
                     .limit(limit)
                     .offset(offset),
@@ -48,18 +93,18 @@ async fn get_leaderboard(
         .map(|(total,)| total as u64)
         .unwrap_or(0);
 
-    let mut rank_xp = rows.first().map(|&(_, xp)| xp).unwrap_or(0);
-    let mut rank = rank_of(db, base_query, rank_xp).await?;
+    let mut rank_value = rows.first().map(|&(_, value)| value).unwrap_or(0);
+    let mut rank = rank_of(db, base_query, score, rank_value).await?;
 
-    let leaderboard = rows.into_iter().enumerate().map(|(i, (id, xp))| {
-        if xp < rank_xp {
+    let leaderboard = rows.into_iter().enumerate().map(|(i, (id, value))| {
+        if score.is_worse(value, rank_value) {
             rank = offset + i as u64 + 1;
-            rank_xp = xp;
+            rank_value = value;
         }
         (
             id,
             Rank {
-                score: xp as _,
+                score: value as _,
                 rank,
             },
         )
@@ -95,15 +140,20 @@ pub async fn get_leaderboard_user(
 
     Ok(Rank {
         score: xp as _,
-        rank: rank_of(db, base_query, xp).await?,
+        rank: rank_of(db, base_query, &Score::xp(), xp).await?,
     })
 }
 
 async fn rank_of(
     db: &DatabaseTransaction,
     mut base_query: SelectStatement,
-    xp: i64,
+    score: &Score,
+    value: i64,
 ) -> anyhow::Result<u64> {
+    let better = match score.order {
+        Order::Asc => BinOper::SmallerThan,
+        _ => BinOper::GreaterThan,
+    };
     Ok(db
         .query_one(
             db.get_database_backend().build(
@@ -111,11 +161,7 @@ async fn rank_of(
                     .expr(Expr::col(Alias::new("user_id")).count())
                     .from_subquery(
                         base_query
-                            .and_having(
-                                Expr::col(Alias::new("xp"))
-                                    .sum()
-                                    .binary(BinOper::GreaterThan, xp),
-                            )
+                            .and_having(score.aggregate.clone().binary(better, value))
                             .to_owned(),
                         Alias::new("x"),
                     ),
@@ -140,3 +186,60 @@ async fn resolve_user(
         rank: rank.into(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::{Database, TransactionTrait};
+
+    use super::*;
+
+    fn usage() -> Score {
+        Score {
+            column: "score",
+            aggregate: Expr::col(Alias::new("usage")).min(),
+            order: Order::Asc,
+        }
+    }
+
+    #[test]
+    fn lower_scores_are_better_in_ascending_order() {
+        assert!(usage().is_worse(20, 10));
+        assert!(!usage().is_worse(10, 20));
+        assert!(!usage().is_worse(10, 10));
+        assert!(Score::xp().is_worse(10, 20));
+        assert!(!Score::xp().is_worse(20, 10));
+        assert!(!Score::xp().is_worse(10, 10));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a PostgreSQL database in DATABASE_URL"]
+    async fn rank_of_ascending_score() {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL is not set");
+        let txn = Database::connect(url).await.unwrap().begin().await.unwrap();
+        txn.execute_unprepared(
+            "CREATE TEMPORARY TABLE scores (user_id uuid, usage int8, ts timestamp)
+                 ON COMMIT DROP;
+             INSERT INTO scores VALUES
+                 ('00000000-0000-0000-0000-000000000001', 30, now()),
+                 ('00000000-0000-0000-0000-000000000001', 10, now()),
+                 ('00000000-0000-0000-0000-000000000002', 20, now()),
+                 ('00000000-0000-0000-0000-000000000003', 20, now()),
+                 ('00000000-0000-0000-0000-000000000004', 40, now());",
+        )
+        .await
+        .unwrap();
+        let base_query = Query::select()
+            .column(Alias::new("user_id"))
+            .expr_as(Expr::col(Alias::new("usage")).min(), Alias::new("score"))
+            .expr_as(Expr::col(Alias::new("ts")).min(), Alias::new("last_update"))
+            .from(Alias::new("scores"))
+            .group_by_col(Alias::new("user_id"))
+            .to_owned();
+
+        let score = usage();
+        for (value, rank) in [(10, 1), (20, 2), (30, 4), (40, 4), (50, 5)] {
+            let result = rank_of(&txn, base_query.clone(), &score, value);
+            assert_eq!(result.await.unwrap(), rank, "{value}");
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use entity::{
+    challenges_coding_challenge_result, challenges_coding_challenge_submissions,
+    challenges_subtasks, sea_orm_active_enums::ChallengesVerdictVariant,
+};
+use lib::services::Services;
+use schemas::challenges::leaderboard::{Leaderboard, PerformanceMetric};
+use sea_orm::{
+    sea_query::{Alias, Expr, Query, SelectStatement, SimpleExpr},
+    DatabaseTransaction, Iden, Order, Value,
+};
+
+use super::{get_ranked_leaderboard, Score};
+
+fn metric_column(metric: PerformanceMetric) -> challenges_coding_challenge_result::Column {
+    match metric {
+        PerformanceMetric::RunTime => challenges_coding_challenge_result::Column::RunTime,
+        PerformanceMetric::RunMemory => challenges_coding_challenge_result::Column::RunMemory,
+    }
+}
+
+/// Rank users by the best resource usage of their accepted submissions of a
+/// coding challenge. Only the latest result of each submission is considered
+/// and submissions of the creator of the coding challenge are ignored.
+fn get_base_query(
+    subtask: &challenges_subtasks::Model,
+    metric: PerformanceMetric,
+    environment: Option<&str>,
+) -> SelectStatement {
+    let usage = Expr::col((
+        challenges_coding_challenge_result::Entity,
+        metric_column(metric),
+    ));
+    let creator = Expr::col((
+        challenges_coding_challenge_submissions::Entity,
+        challenges_coding_challenge_submissions::Column::Creator,
+    ));
+    let mut query = Query::select()
+        .expr_as(creator.clone(), Alias::new("user_id"))
+        .expr_as(
+            usage.clone().min().cast_as(Alias::new("int8")),
+            Alias::new("score"),
+        )
+        .expr_as(
+            Expr::col((
+                challenges_coding_challenge_submissions::Entity,
+                challenges_coding_challenge_submissions::Column::CreationTimestamp,
+            ))
+            .min(),
+            Alias::new("last_update"),
+        )
+        .from(challenges_coding_challenge_result::Entity)
+        .inner_join(
+            challenges_coding_challenge_submissions::Entity,
+            Expr::col((
+                challenges_coding_challenge_result::Entity,
+                challenges_coding_challenge_result::Column::SubmissionId,
+            ))
+            .equals((
+                challenges_coding_challenge_submissions::Entity,
+                challenges_coding_challenge_submissions::Column::Id,
+            )),
+        )
+        .and_where(
+            Expr::col(challenges_coding_challenge_submissions::Column::SubtaskId).eq(subtask.id),
+        )
+        .and_where(creator.ne(subtask.creator))
+        .and_where(
+            Expr::col(challenges_coding_challenge_result::Column::Verdict).eq(
+                SimpleExpr::Constant(Value::String(Some(
+                    ChallengesVerdictVariant::Ok.to_string().into(),
+                ))),
+            ),
+        )
+        .and_where(usage.is_not_null())
+        .and_where(Expr::exists(get_newer_result_query()).not())
+        .to_owned();
+
+    if let Some(environment) = environment {
+        query.and_where(
+            Expr::col(challenges_coding_challenge_submissions::Column::Environment).eq(environment),
+        );
+    }
+
+    query
+        .group_by_col((
+            challenges_coding_challenge_submissions::Entity,
+            challenges_coding_challenge_submissions::Column::Creator,
+        ))
+        .to_owned()
+}
+
+/// Select the results of the same submission that are newer than the result
+/// of the base query.
+fn get_newer_result_query() -> SelectStatement {
+    let newer = Alias::new("newer");
+    Query::select()
+        .expr(Expr::val(1))
+        .from_as(challenges_coding_challenge_result::Entity, newer.clone())
+        .and_where(
+            Expr::col((
+                newer.clone(),
+                challenges_coding_challenge_result::Column::SubmissionId,
+            ))
+            .equals((
+                challenges_coding_challenge_result::Entity,
+                challenges_coding_challenge_result::Column::SubmissionId,
+            )),
+        )
+        .and_where(
+            Expr::col((
+                newer,
+                challenges_coding_challenge_result::Column::CreationTimestamp,
+            ))
+            .gt(Expr::col((
+                challenges_coding_challenge_result::Entity,
+                challenges_coding_challenge_result::Column::CreationTimestamp,
+            ))),
+        )
+        .to_owned()
+}
+
+pub async fn get_performance_leaderboard(
+    db: &DatabaseTransaction,
+    services: &Services,
+    subtask: &challenges_subtasks::Model,
+    metric: PerformanceMetric,
+    environment: Option<&str>,
+    limit: u64,
+    offset: u64,
+) -> anyhow::Result<Leaderboard> {
+    let base_query = get_base_query(subtask, metric, environment);
+    let score = Score {
+        column: "score",
+        aggregate: Expr::col((
+            challenges_coding_challenge_result::Entity,
+            metric_column(metric),
+        ))
+        .min(),
+        order: Order::Asc,
+    };
+    get_ranked_leaderboard(db, services, base_query, &score, limit, offset).await
+}
@@ -0,0 +1,65 @@
+//! Metrics of the challenges microservice.
+
+use lib::metrics::{self, Counter, Gauge, Histogram, Metric, DURATION_BUCKETS};
+use sea_orm::{ConnectionTrait, DbErr};
+
+use super::judge_queue;
+
+pub static JUDGE_QUEUE_WAITING: Gauge = Gauge::new(
+    "judge_queue_waiting",
+    "Number of submissions waiting to be judged.",
+    &[],
+);
+
+pub static JUDGE_QUEUE_ACTIVE: Gauge = Gauge::new(
+    "judge_queue_active",
+    "Number of submissions that are currently being judged.",
+    &[],
+);
+
+pub static JUDGE_WORKERS: Gauge = Gauge::new(
+    "judge_workers",
+    "Number of submissions all active judge workers can judge concurrently.",
+    &[],
+);
+
+pub static JUDGE_VERDICTS: Counter = Counter::new(
+    "judge_verdicts_total",
+    "Number of judged submissions per verdict.",
+    &["verdict"],
+);
+
+pub static JUDGE_DURATION: Histogram = Histogram::new(
+    "judge_duration_seconds",
+    "Time spent judging a submission.",
+    &[],
+    DURATION_BUCKETS,
+);
+
+pub static SANDKASTEN_ERRORS: Counter = Counter::new(
+    "sandkasten_errors_total",
+    "Number of unexpected errors returned by Sandkasten.",
+    &["operation"],
+);
+
+static METRICS: &[&dyn Metric] = &[
+    &JUDGE_QUEUE_WAITING,
+    &JUDGE_QUEUE_ACTIVE,
+    &JUDGE_WORKERS,
+    &JUDGE_VERDICTS,
+    &JUDGE_DURATION,
+    &SANDKASTEN_ERRORS,
+];
+
+/// Update the judge queue metrics and render all metrics in the Prometheus
+/// text format.
+pub async fn render(
+    db: &impl ConnectionTrait,
+    lease_duration: chrono::Duration,
+) -> Result<String, DbErr> {
+    let status = judge_queue::status(db, lease_duration).await?;
+    JUDGE_QUEUE_WAITING.set(&[], status.waiting as _);
+    JUDGE_QUEUE_ACTIVE.set(&[], status.active as _);
+    JUDGE_WORKERS.set(&[], status.workers as _);
+    Ok(metrics::render(metrics::METRICS.iter().chain(METRICS)))
+}
@@ -1,5 +1,11 @@
 pub mod course_tasks;
 pub mod judge;
+pub mod judge_queue;
 pub mod leaderboard;
+pub mod metrics;
+pub mod plagiarism;
+pub mod rate_limit;
+pub mod stats;
+pub mod submission_events;
 pub mod subtasks;
 pub mod tasks;
@@ -0,0 +1,401 @@
+//! Detection of similar coding challenge submissions using winnowing
+//! fingerprints of normalized token streams.
+
+use std::{
+    collections::{HashMap, HashSet},
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+use chrono::{NaiveDateTime, Utc};
+use entity::{
+    challenges_coding_challenge_result, challenges_coding_challenge_similarities,
+    challenges_coding_challenge_similarity_scan, challenges_coding_challenge_submission_files,
+    challenges_coding_challenge_submissions, sea_orm_active_enums::ChallengesVerdict,
+};
+use sea_orm::{
+    sea_query::{Expr, LockBehavior, LockType, OnConflict},
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait, Unchanged,
+};
+use thiserror::Error;
+use tokio::task::JoinError;
+use uuid::Uuid;
+
+/// The number of consecutive tokens that are hashed together.
+const K: usize = 12;
+/// The number of consecutive hashes from which winnowing selects one
+/// fingerprint.
+const WINDOW: usize = 8;
+
+/// Words that are kept as they are instead of being normalized like
+/// identifiers, because they describe the structure of a program.
+const KEYWORDS: &[&str] = &[
+    "and", "break", "case", "catch", "class", "const", "continue", "def", "do", "elif", "else",
+    "enum", "except", "fn", "for", "func", "function", "if", "impl", "import", "in", "lambda",
+    "let", "loop", "match", "mut", "new", "not", "or", "pass", "return", "static", "struct",
+    "switch", "try", "var", "while", "with", "yield",
+];
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum Token<'a> {
+    Keyword(&'a str),
+    Identifier,
+    Number,
+    String,
+    Symbol(char),
+}
+
+/// Split source code into tokens. Identifiers, numbers and string literals
+/// are normalized so that renaming variables or changing constants does not
+/// affect the result. Whitespace and comments are ignored.
+fn tokenize(code: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut chars = code.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        match c {
+            _ if c.is_whitespace() => {}
+            '#' => while chars.next_if(|&(_, c)| c != '\n').is_some() {},
+            '/' if chars.next_if(|&(_, c)| c == '/').is_some() => {
+                while chars.next_if(|&(_, c)| c != '\n').is_some() {}
+            }
+            '/' if chars.next_if(|&(_, c)| c == '*').is_some() => {
+                let mut prev = '\0';
+                for (_, c) in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            '"' | '\'' | '`' => {
+                let mut escaped = false;
+                for (_, x) in chars.by_ref() {
+                    if x == c && !escaped {
+                        break;
+                    }
+                    escaped = x == '\\' && !escaped;
+                }
+                tokens.push(Token::String);
+            }
+            _ if c.is_ascii_digit() => {
+                while chars
+                    .next_if(|&(_, c)| c.is_ascii_alphanumeric() || c == '.' || c == '_')
+                    .is_some()
+                {}
+                tokens.push(Token::Number);
+            }
+            _ if c.is_alphabetic() || c == '_' => {
+                let mut end = start + c.len_utf8();
+                while let Some((i, c)) = chars.next_if(|&(_, c)| c.is_alphanumeric() || c == '_') {
+                    end = i + c.len_utf8();
+                }
+                let word = &code[start..end];
+                tokens.push(match KEYWORDS.contains(&word) {
+                    true => Token::Keyword(word),
+                    false => Token::Identifier,
+                });
+            }
+            _ => tokens.push(Token::Symbol(c)),
+        }
+    }
+    tokens
+}
+
+/// Compute the winnowing fingerprints of a program.
+pub fn fingerprint(code: &str) -> HashSet<u64> {
+    let tokens = tokenize(code);
+    let hashes = tokens
+        .windows(K.min(tokens.len()).max(1))
+        .map(|gram| {
+            let mut hasher = DefaultHasher::new();
+            gram.hash(&mut hasher);
+            hasher.finish()
+        })
+        .collect::<Vec<_>>();
+    hashes
+        .windows(WINDOW.min(hashes.len()).max(1))
+        .filter_map(|window| window.iter().rev().min().copied())
+        .collect()
+}
+
+/// Return the similarity of two programs given their fingerprints as the
+/// share of common fingerprints (between `0` and `1`).
+pub fn similarity(a: &HashSet<u64>, b: &HashSet<u64>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+struct AcceptedSubmission {
+    id: Uuid,
+    creator: Uuid,
+    environment: String,
+    judged: NaiveDateTime,
+    code: String,
+}
+
+/// Compare the submissions that have been accepted since the last scan using
+/// [`detect_similarities`]. The timestamp of the last scan is stored in the
+/// database and a scan is claimed by locking its row and advancing it before
+/// the submissions are compared, so only one instance scans the same
+/// submissions. Return `None` if another instance is claiming a scan.
+pub async fn detect_new_similarities(
+    db: &DatabaseConnection,
+    threshold: f64,
+) -> Result<Option<usize>, DetectSimilaritiesError> {
+    let txn = db.begin().await?;
+    let Some(scan) = challenges_coding_challenge_similarity_scan::Entity::find()
+        .lock_with_behavior(LockType::Update, LockBehavior::SkipLocked)
+        .one(&txn)
+        .await?
+    else {
+        return Ok(None);
+    };
+    let start = Utc::now().naive_utc();
+    challenges_coding_challenge_similarity_scan::ActiveModel {
+        id: Unchanged(scan.id),
+        last_scan: Set(Some(start)),
+    }
+    .update(&txn)
+    .await?;
+    txn.commit().await?;
+
+    let found = detect_similarities(db, threshold, scan.last_scan).await;
+    if found.is_err() {
+        // give the claimed submissions back to the next scan, unless another
+        // scan has been claimed in the meantime
+        challenges_coding_challenge_similarity_scan::Entity::update_many()
+            .col_expr(
+                challenges_coding_challenge_similarity_scan::Column::LastScan,
+                Expr::value(scan.last_scan),
+            )
+            .filter(challenges_coding_challenge_similarity_scan::Column::Id.eq(scan.id))
+            .filter(challenges_coding_challenge_similarity_scan::Column::LastScan.eq(start))
+            .exec(db)
+            .await?;
+    }
+
+    found.map(Some)
+}
+
+/// Compare all accepted submissions of each coding challenge written for the
+/// same environment by different users and store all pairs whose similarity
+/// reaches `threshold`. If `since` is set, only pairs containing a submission
+/// that has been accepted after this timestamp are compared. Return the number
+/// of pairs found.
+pub async fn detect_similarities(
+    db: &impl ConnectionTrait,
+    threshold: f64,
+    since: Option<NaiveDateTime>,
+) -> Result<usize, DetectSimilaritiesError> {
+    let mut query = challenges_coding_challenge_submissions::Entity::find()
+        .select_only()
+        .column(challenges_coding_challenge_submissions::Column::SubtaskId)
+        .distinct()
+        .inner_join(challenges_coding_challenge_result::Entity)
+        .filter(challenges_coding_challenge_result::Column::Verdict.eq(ChallengesVerdict::Ok));
+    if let Some(since) = since {
+        query =
+            query.filter(challenges_coding_challenge_result::Column::CreationTimestamp.gt(since));
+    }
+    let challenges = query.into_tuple::<Uuid>().all(db).await?;
+
+    let mut found = 0;
+    for challenge_id in challenges {
+        let submissions = get_accepted_submissions(db, challenge_id).await?;
+        let similarities = tokio::task::spawn_blocking(move || {
+            compare_submissions(challenge_id, &submissions, threshold, since)
+        })
+        .await?;
+
+        found += similarities.len();
+        for chunk in similarities.chunks(256) {
+            challenges_coding_challenge_similarities::Entity::insert_many(chunk.to_vec())
+                .on_conflict(
+                    OnConflict::columns([
+                        challenges_coding_challenge_similarities::Column::SubmissionA,
+                        challenges_coding_challenge_similarities::Column::SubmissionB,
+                    ])
+                    .do_nothing()
+                    .to_owned(),
+                )
+                .do_nothing()
+                .exec(db)
+                .await?;
+        }
+    }
+
+    Ok(found)
+}
+
+/// Fingerprint the accepted submissions of a coding challenge and return all
+/// pairs that should be stored by [`detect_similarities`]. This is CPU-bound
+/// and must not run on the async runtime.
+fn compare_submissions(
+    challenge_id: Uuid,
+    submissions: &[AcceptedSubmission],
+    threshold: f64,
+    since: Option<NaiveDateTime>,
+) -> Vec<challenges_coding_challenge_similarities::ActiveModel> {
+    let fingerprints = submissions
+        .iter()
+        .map(|submission| fingerprint(&submission.code))
+        .collect::<Vec<_>>();
+    let mut similarities = Vec::new();
+    for (i, a) in submissions.iter().enumerate() {
+        for (j, b) in submissions.iter().enumerate().skip(i + 1) {
+            if a.creator == b.creator
+                || a.environment != b.environment
+                || since.is_some_and(|since| a.judged <= since && b.judged <= since)
+            {
+                continue;
+            }
+            let value = similarity(&fingerprints[i], &fingerprints[j]);
+            if value < threshold {
+                continue;
+            }
+            let (a, b) = if a.id < b.id { (a, b) } else { (b, a) };
+            similarities.push(challenges_coding_challenge_similarities::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                challenge_id: Set(challenge_id),
+                submission_a: Set(a.id),
+                submission_b: Set(b.id),
+                user_a: Set(a.creator),
+                user_b: Set(b.creator),
+                similarity: Set(value),
+                creation_timestamp: Set(Utc::now().naive_utc()),
+                resolved: Set(false),
+            });
+        }
+    }
+    similarities
+}
+
+/// Return all submissions of a coding challenge whose latest result is
+/// accepted together with their code and additional files.
+async fn get_accepted_submissions(
+    db: &impl ConnectionTrait,
+    challenge_id: Uuid,
+) -> Result<Vec<AcceptedSubmission>, DbErr> {
+    let mut latest = HashMap::new();
+    for (submission, result) in challenges_coding_challenge_submissions::Entity::find()
+        .find_also_related(challenges_coding_challenge_result::Entity)
+        .filter(challenges_coding_challenge_submissions::Column::SubtaskId.eq(challenge_id))
+        .order_by_asc(challenges_coding_challenge_result::Column::CreationTimestamp)
+        .all(db)
+        .await?
+    {
+        let Some(result) = result else { continue };
+        latest.insert(submission.id, (submission, result));
+    }
+    let submissions = latest
+        .into_iter()
+        .filter(|(_, (_, result))| result.verdict == ChallengesVerdict::Ok)
+        .map(|(id, (submission, result))| (id, (submission, result.creation_timestamp)))
+        .collect::<HashMap<_, _>>();
+
+    let mut files = HashMap::<Uuid, String>::new();
+    for file in challenges_coding_challenge_submission_files::Entity::find()
+        .filter(
+            challenges_coding_challenge_submission_files::Column::SubmissionId
+                .is_in(submissions.keys().copied()),
+        )
+        .order_by_asc(challenges_coding_challenge_submission_files::Column::Name)
+        .all(db)
+        .await?
+    {
+        let content = files.entry(file.submission_id).or_default();
+        content.push('\n');
+        content.push_str(&file.content);
+    }
+
+    Ok(submissions
+        .into_values()
+        .map(|(submission, judged)| {
+            let mut code = submission.code;
+            if let Some(files) = files.get(&submission.id) {
+                code.push_str(files);
+            }
+            AcceptedSubmission {
+                id: submission.id,
+                creator: submission.creator,
+                environment: submission.environment,
+                judged,
+                code,
+            }
+        })
+        .collect())
+}
+
+#[derive(Debug, Error)]
+pub enum DetectSimilaritiesError {
+    #[error("database error: {0}")]
+    Db(#[from] DbErr),
+    #[error("failed to compare submissions: {0}")]
+    Join(#[from] JoinError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROGRAM: &str = r#"
+        n = int(input())
+        total = 0
+        for i in range(1, n + 1):
+            if i % 3 == 0 or i % 5 == 0:
+                total += i
+        print(total)
+    "#;
+
+    #[test]
+    fn tokenize_normalizes_identifiers_and_literals() {
+        assert_eq!(
+            tokenize("let x = foo(\"a b\", 42); // comment"),
+            [
+                Token::Keyword("let"),
+                Token::Identifier,
+                Token::Symbol('='),
+                Token::Identifier,
+                Token::Symbol('('),
+                Token::String,
+                Token::Symbol(','),
+                Token::Number,
+                Token::Symbol(')'),
+                Token::Symbol(';'),
+            ]
+        );
+    }
+
+    #[test]
+    fn renamed_program_is_identical() {
+        let renamed = r#"
+            # read the input
+            limit = int(input())
+            s = 0
+            for x in range(1, limit + 1):
+                if x % 3 == 0 or x % 5 == 0: s += x
+            print(s)
+        "#;
+        assert_eq!(
+            similarity(&fingerprint(PROGRAM), &fingerprint(renamed)),
+            1.0
+        );
+    }
+
+    #[test]
+    fn different_programs_are_not_similar() {
+        let other = r#"
+            n = int(input())
+            print(sum(x for x in range(n + 1) if not (x % 3 and x % 5)))
+        "#;
+        assert!(similarity(&fingerprint(PROGRAM), &fingerprint(other)) < 0.5);
+    }
+
+    #[test]
+    fn empty_programs_are_not_similar() {
+        assert_eq!(similarity(&fingerprint(""), &fingerprint("  \n")), 0.0);
+    }
+}
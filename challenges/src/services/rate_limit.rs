@@ -0,0 +1,39 @@
+use lib::redis::RedisConnection;
+use redis::{AsyncCommands, ExistenceCheck, RedisResult, SetExpiry, SetOptions};
+
+/// Limits how often an action can be performed, shared between all instances
+/// via redis.
+#[derive(Clone)]
+pub struct RateLimit {
+    redis: RedisConnection,
+}
+
+impl RateLimit {
+    pub async fn new(url: &str) -> RedisResult<Self> {
+        Ok(Self {
+            redis: RedisConnection::new(url).await?,
+        })
+    }
+
+    /// Try to perform the action identified by `key`, which is then blocked
+    /// for the next `timeout` seconds. Return the number of seconds left if
+    /// the action is still blocked.
+    pub async fn acquire(&self, key: &str, timeout: u64) -> RedisResult<Option<u64>> {
+        let key = format!("challenges:rate_limit:{key}");
+        let mut redis = self.redis.clone();
+        let acquired: Option<String> = redis
+            .set_options(
+                &key,
+                "",
+                SetOptions::default()
+                    .conditional_set(ExistenceCheck::NX)
+                    .with_expiration(SetExpiry::EX(timeout as _)),
+            )
+            .await?;
+        if acquired.is_some() {
+            return Ok(None);
+        }
+        let ttl: i64 = redis.ttl(&key).await?;
+        Ok(Some(ttl.max(1) as _))
+    }
+}
@@ -0,0 +1,251 @@
+//! Statistics about the submissions of coding challenges.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::NaiveDateTime;
+use entity::{
+    challenges_coding_challenge_result, challenges_coding_challenge_submissions,
+    challenges_subtasks, sea_orm_active_enums::ChallengesVerdict,
+};
+use schemas::challenges::coding_challenges::{CodingChallengeStats, SubmissionStats, VerdictCount};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DbErr, EntityTrait, Iterable, QueryFilter, QueryOrder,
+    QuerySelect,
+};
+use uuid::Uuid;
+
+pub struct JudgedSubmission {
+    pub subtask_id: Uuid,
+    pub creator: Uuid,
+    pub environment: String,
+    pub creation_timestamp: NaiveDateTime,
+    /// The verdict of the latest result. Empty if the submission has not been
+    /// judged yet.
+    pub verdict: Option<ChallengesVerdict>,
+}
+
+/// Return the submissions of the given coding challenges together with the
+/// verdicts of their latest results. Submissions of the creator of a coding
+/// challenge are ignored.
+pub async fn get_judged_submissions(
+    db: &impl ConnectionTrait,
+    subtasks: &[challenges_subtasks::Model],
+) -> Result<Vec<JudgedSubmission>, DbErr> {
+    let creators = subtasks
+        .iter()
+        .map(|subtask| (subtask.id, subtask.creator))
+        .collect::<HashMap<_, _>>();
+
+    let mut verdicts = HashMap::new();
+    for (submission_id, verdict) in challenges_coding_challenge_result::Entity::find()
+        .select_only()
+        .column(challenges_coding_challenge_result::Column::SubmissionId)
+        .column(challenges_coding_challenge_result::Column::Verdict)
+        .inner_join(challenges_coding_challenge_submissions::Entity)
+        .filter(
+            challenges_coding_challenge_submissions::Column::SubtaskId
+                .is_in(creators.keys().copied()),
+        )
+        .order_by_asc(challenges_coding_challenge_result::Column::CreationTimestamp)
+        .into_tuple::<(Uuid, ChallengesVerdict)>()
+        .all(db)
+        .await?
+    {
+        verdicts.insert(submission_id, verdict);
+    }
+
+    Ok(challenges_coding_challenge_submissions::Entity::find()
+        .select_only()
+        .column(challenges_coding_challenge_submissions::Column::Id)
+        .column(challenges_coding_challenge_submissions::Column::SubtaskId)
+        .column(challenges_coding_challenge_submissions::Column::Creator)
+        .column(challenges_coding_challenge_submissions::Column::Environment)
+        .column(challenges_coding_challenge_submissions::Column::CreationTimestamp)
+        .filter(
+            challenges_coding_challenge_submissions::Column::SubtaskId
+                .is_in(creators.keys().copied()),
+        )
+        .into_tuple::<(Uuid, Uuid, Uuid, String, NaiveDateTime)>()
+        .all(db)
+        .await?
+        .into_iter()
+        .filter(|(_, subtask_id, creator, _, _)| creators.get(subtask_id) != Some(creator))
+        .map(
+            |(id, subtask_id, creator, environment, creation_timestamp)| JudgedSubmission {
+                subtask_id,
+                creator,
+                environment,
+                creation_timestamp,
+                verdict: verdicts.get(&id).copied(),
+            },
+        )
+        .collect())
+}
+
+/// Aggregate the statistics of all submissions and of the submissions
+/// written for each environment.
+pub fn coding_challenge_stats(submissions: &[JudgedSubmission]) -> CodingChallengeStats {
+    let mut environments = HashMap::<_, Vec<_>>::new();
+    for submission in submissions {
+        environments
+            .entry(submission.environment.clone())
+            .or_default()
+            .push(submission);
+    }
+    CodingChallengeStats {
+        total: submission_stats(submissions.iter().collect()),
+        environments: environments
+            .into_iter()
+            .map(|(environment, submissions)| (environment, submission_stats(submissions)))
+            .collect(),
+    }
+}
+
+fn submission_stats(mut submissions: Vec<&JudgedSubmission>) -> SubmissionStats {
+    submissions.sort_by_key(|submission| submission.creation_timestamp);
+
+    let mut users = HashSet::new();
+    let mut solved_by = HashSet::new();
+    // the number of attempts of each user for each coding challenge, or
+    // `None` after the first accepted submission
+    let mut attempts = HashMap::new();
+    let mut attempts_until_accepted = Vec::new();
+    for submission in &submissions {
+        users.insert(submission.creator);
+        let count = attempts
+            .entry((submission.subtask_id, submission.creator))
+            .or_insert(Some(0));
+        let Some(n) = count else { continue };
+        *n += 1;
+        if submission.verdict == Some(ChallengesVerdict::Ok) {
+            attempts_until_accepted.push(*n);
+            solved_by.insert(submission.creator);
+            *count = None;
+        }
+    }
+
+    let count = |verdict: ChallengesVerdict| {
+        submissions
+            .iter()
+            .filter(|submission| submission.verdict == Some(verdict))
+            .count() as u64
+    };
+    let judged = submissions
+        .iter()
+        .filter(|submission| submission.verdict.is_some())
+        .count() as u64;
+    let accepted = count(ChallengesVerdict::Ok);
+    SubmissionStats {
+        submissions: submissions.len() as _,
+        users: users.len() as _,
+        judged,
+        accepted,
+        acceptance_rate: match judged {
+            0 => 0.0,
+            _ => accepted as f64 / judged as f64,
+        },
+        solved_by: solved_by.len() as _,
+        verdicts: ChallengesVerdict::iter()
+            .map(|verdict| VerdictCount {
+                verdict,
+                count: count(verdict),
+            })
+            .filter(|verdict| verdict.count > 0)
+            .collect(),
+        median_attempts: median(attempts_until_accepted),
+    }
+}
+
+fn median(mut values: Vec<u64>) -> Option<f64> {
+    values.sort_unstable();
+    let mid = values.len() / 2;
+    match values.len() {
+        0 => None,
+        n if n % 2 == 0 => Some((values[mid - 1] + values[mid]) as f64 / 2.0),
+        _ => Some(values[mid] as f64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+
+    use super::*;
+
+    fn submission(
+        subtask: u128,
+        creator: u128,
+        environment: &str,
+        time: i64,
+        verdict: Option<ChallengesVerdict>,
+    ) -> JudgedSubmission {
+        JudgedSubmission {
+            subtask_id: Uuid::from_u128(subtask),
+            creator: Uuid::from_u128(creator),
+            environment: environment.into(),
+            creation_timestamp: DateTime::from_timestamp(time, 0).unwrap().naive_utc(),
+            verdict,
+        }
+    }
+
+    #[test]
+    fn empty() {
+        let stats = coding_challenge_stats(&[]);
+        assert_eq!(stats.total.submissions, 0);
+        assert_eq!(stats.total.acceptance_rate, 0.0);
+        assert_eq!(stats.total.median_attempts, None);
+        assert!(stats.environments.is_empty());
+    }
+
+    #[test]
+    fn aggregates_submissions() {
+        use ChallengesVerdict::*;
+        let submissions = [
+            submission(1, 1, "python", 3, Some(Ok)),
+            submission(1, 1, "python", 1, Some(WrongAnswer)),
+            submission(1, 1, "python", 2, Some(TimeLimitExceeded)),
+            submission(1, 1, "python", 4, Some(WrongAnswer)),
+            submission(1, 2, "rust", 1, Some(Ok)),
+            submission(1, 3, "rust", 1, Some(WrongAnswer)),
+            submission(1, 3, "rust", 2, None),
+        ];
+        let stats = coding_challenge_stats(&submissions);
+
+        let total = &stats.total;
+        assert_eq!(total.submissions, 7);
+        assert_eq!(total.users, 3);
+        assert_eq!(total.judged, 6);
+        assert_eq!(total.accepted, 2);
+        assert_eq!(total.acceptance_rate, 2.0 / 6.0);
+        assert_eq!(total.solved_by, 2);
+        assert_eq!(total.median_attempts, Some(2.0));
+        let verdicts = total
+            .verdicts
+            .iter()
+            .map(|x| (x.verdict, x.count))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            verdicts,
+            [(Ok, 2), (TimeLimitExceeded, 1), (WrongAnswer, 3)]
+        );
+
+        let rust = &stats.environments["rust"];
+        assert_eq!(rust.submissions, 3);
+        assert_eq!(rust.users, 2);
+        assert_eq!(rust.acceptance_rate, 0.5);
+        assert_eq!(rust.median_attempts, Some(1.0));
+    }
+
+    #[test]
+    fn attempts_are_counted_per_challenge() {
+        use ChallengesVerdict::*;
+        let submissions = [
+            submission(1, 1, "python", 1, Some(WrongAnswer)),
+            submission(1, 1, "python", 2, Some(Ok)),
+            submission(2, 1, "python", 3, Some(Ok)),
+        ];
+        let stats = coding_challenge_stats(&submissions);
+        assert_eq!(stats.total.solved_by, 1);
+        assert_eq!(stats.total.median_attempts, Some(1.5));
+    }
+}
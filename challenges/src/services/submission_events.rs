@@ -0,0 +1,86 @@
+use futures::{Stream, StreamExt};
+use lib::redis::RedisConnection;
+use poem_openapi::types::{ParseFromJSON, ToJSON};
+use redis::{AsyncCommands, Client, RedisResult};
+use schemas::challenges::coding_challenges::SubmissionEvent;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+const QUEUE_CHANNEL: &str = "challenges:judge_queue";
+
+/// Publishes updates on the status of submissions via redis, so that every
+/// instance can forward them to its clients.
+#[derive(Clone)]
+pub struct SubmissionEvents {
+    client: Client,
+    redis: RedisConnection,
+}
+
+#[derive(Debug)]
+pub enum Message {
+    /// An event of the subscribed submission.
+    Event(SubmissionEvent),
+    /// The judge queue has changed, so the queue position of the submission
+    /// might have changed as well.
+    QueueChanged,
+}
+
+impl SubmissionEvents {
+    pub async fn new(url: &str) -> RedisResult<Self> {
+        Ok(Self {
+            client: Client::open(url)?,
+            redis: RedisConnection::new(url).await?,
+        })
+    }
+
+    /// Publish an event of a submission. Errors are logged and otherwise
+    /// ignored, as clients can always fall back to polling.
+    pub async fn publish(&self, submission_id: Uuid, event: &SubmissionEvent) {
+        self.send(&submission_channel(submission_id), event.to_json_string())
+            .await;
+    }
+
+    /// Notify all subscribers that the judge queue has changed.
+    pub async fn publish_queue_changed(&self) {
+        self.send(QUEUE_CHANNEL, String::new()).await;
+    }
+
+    /// Subscribe to the events of a submission and to changes of the judge
+    /// queue.
+    pub async fn subscribe(
+        &self,
+        submission_id: Uuid,
+    ) -> RedisResult<impl Stream<Item = Message> + Send + 'static> {
+        let mut pubsub = self.client.get_async_pubsub().await?;
+        pubsub.subscribe(submission_channel(submission_id)).await?;
+        pubsub.subscribe(QUEUE_CHANNEL).await?;
+        Ok(pubsub.into_on_message().filter_map(|msg| async move {
+            if msg.get_channel_name() == QUEUE_CHANNEL {
+                return Some(Message::QueueChanged);
+            }
+            let payload = msg.get_payload::<String>().ok()?;
+            match SubmissionEvent::parse_from_json_string(&payload) {
+                Ok(event) => Some(Message::Event(event)),
+                Err(err) => {
+                    warn!("received invalid submission event: {}", err.into_message());
+                    None
+                }
+            }
+        }))
+    }
+
+    async fn send(&self, channel: &str, payload: String) {
+        if let Err(err) = self
+            .redis
+            .clone()
+            .publish::<_, _, ()>(channel, payload)
+            .await
+        {
+            error!("failed to publish to {channel}: {err}");
+        }
+    }
+}
+
+fn submission_channel(submission_id: Uuid) -> String {
+    format!("challenges:submissions:{submission_id}")
+}
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use anyhow::Context;
-use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
 use entity::{
     challenges_ban, challenges_subtasks, challenges_tasks, challenges_user_subtasks,
     sea_orm_active_enums::{ChallengesBanAction, ChallengesSubtaskType},
@@ -18,8 +18,9 @@ use schemas::challenges::subtasks::{
     CreateSubtaskRequest, Subtask, SubtaskStats, UpdateSubtaskRequest,
 };
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, Condition, DatabaseTransaction, DbErr, EntityTrait, ModelTrait,
-    QueryFilter, QueryOrder, Related, Set, Unchanged,
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseTransaction, DbErr,
+    EntityTrait, ModelTrait, PaginatorTrait, QueryFilter, QueryOrder, Related, Set, Statement,
+    Unchanged,
 };
 use thiserror::Error;
 use uuid::Uuid;
@@ -57,20 +58,34 @@ pub async fn deduct_hearts(
     user: &User,
     subtask: &challenges_subtasks::Model,
 ) -> anyhow::Result<bool> {
+    Ok(pay_hearts(services, config, user, subtask).await?.is_some())
+}
+
+/// Deduct the hearts for an attempt to solve a subtask.
+///
+/// Returns the number of hearts that have been deducted or `None` if the user
+/// does not have enough hearts.
+pub async fn pay_hearts(
+    services: &Services,
+    config: &Config,
+    user: &User,
+    subtask: &challenges_subtasks::Model,
+) -> anyhow::Result<Option<u32>> {
     if subtask.retired
         || user.admin
         || user.id == subtask.creator
         || services.shop.has_premium(user.id).await?
     {
-        return Ok(true);
+        return Ok(Some(0));
     }
 
     let hearts = subtask_hearts(config, subtask.ty);
-    services
+    let success = services
         .shop
         .add_hearts(user.id, -(hearts as i32))
         .await
-        .with_context(|| format!("failed to deduct {hearts} hearts for user {}", user.id))
+        .with_context(|| format!("failed to deduct {hearts} hearts for user {}", user.id))?;
+    Ok(success.then_some(hearts))
 }
 
 fn subtask_hearts(config: &Config, ty: ChallengesSubtaskType) -> u32 {
@@ -89,11 +104,27 @@ pub async fn send_task_rewards(
     user_id: Uuid,
     subtask: &challenges_subtasks::Model,
 ) -> Result<(), SendTaskRewardsError> {
-    if subtask.retired {
+    send_partial_task_rewards(services, db, user_id, subtask, 0.0, 1.0).await
+}
+
+/// Send the share of the rewards of a subtask that a user has earned by
+/// improving their score (between `0` and `1`) from `previous_score` to
+/// `score`.
+pub async fn send_partial_task_rewards(
+    services: &Services,
+    db: &DatabaseTransaction,
+    user_id: Uuid,
+    subtask: &challenges_subtasks::Model,
+    previous_score: f64,
+    score: f64,
+) -> Result<(), SendTaskRewardsError> {
+    if subtask.retired || score <= previous_score {
         return Ok(());
     }
 
-    if subtask.xp != 0 {
+    let xp = reward_share(subtask.xp, previous_score, score);
+    let coins = reward_share(subtask.coins, previous_score, score);
+    if xp != 0 {
         let skills = get_skills(
             services,
             get_parent_task(db, subtask)
@@ -105,19 +136,28 @@ pub async fn send_task_rewards(
         for skill in &skills {
             services
                 .skills
-                .add_skill_progress(user_id, skill, subtask.xp / skills.len() as i64)
+                .add_skill_progress(user_id, skill, xp / skills.len() as i64)
                 .await??;
         }
     }
-    if subtask.coins != 0 {
+    if coins != 0 {
         services
             .shop
-            .add_coins(user_id, subtask.coins, "Challenges / Aufgaben", true)
+            .add_coins(user_id, coins, "Challenges / Aufgaben", true)
             .await??;
     }
     Ok(())
 }
 
+/// Return the part of `amount` that corresponds to the improvement from
+/// `previous_score` to `score`. Rounding is applied to the total share of
+/// each score, so that the top-ups of several improvements add up to the
+/// share of the final score.
+fn reward_share(amount: i64, previous_score: f64, score: f64) -> i64 {
+    let share = |score: f64| (amount as f64 * score.clamp(0.0, 1.0)).round() as i64;
+    share(score) - share(previous_score)
+}
+
 pub async fn get_user_subtasks(
     db: &DatabaseTransaction,
     user_id: Uuid,
@@ -163,6 +203,25 @@ pub async fn update_user_subtask(
     }
 }
 
+/// Lock the user subtask until the end of the transaction, so that concurrent
+/// updates (possibly from other instances) are serialized.
+pub async fn lock_user_subtask(
+    db: &DatabaseTransaction,
+    user_id: Uuid,
+    subtask_id: Uuid,
+) -> Result<(), DbErr> {
+    let (a, b) = user_id.as_u64_pair();
+    let (c, d) = subtask_id.as_u64_pair();
+    let key = (a ^ b ^ (c ^ d).rotate_left(32)) as i64;
+    db.execute(Statement::from_sql_and_values(
+        db.get_database_backend(),
+        "SELECT pg_advisory_xact_lock($1)",
+        [key.into()],
+    ))
+    .await?;
+    Ok(())
+}
+
 pub async fn get_active_ban(
     db: &DatabaseTransaction,
     user: &User,
@@ -195,6 +254,41 @@ pub enum ActiveBan {
     Permanent,
 }
 
+/// Ban a user. The duration of the ban depends on the number of previous
+/// bans of the user for the same action.
+pub async fn ban_user(
+    db: &DatabaseTransaction,
+    user_id: Uuid,
+    action: ChallengesBanAction,
+    ban_days: &[u32],
+    creator: Uuid,
+    reason: String,
+) -> Result<challenges_ban::Model, DbErr> {
+    let now = Utc::now().naive_utc();
+
+    let bans = challenges_ban::Entity::find()
+        .filter(challenges_ban::Column::UserId.eq(user_id))
+        .filter(challenges_ban::Column::Action.eq(action))
+        .count(db)
+        .await?;
+
+    let duration = ban_days
+        .get(bans as usize)
+        .map(|&days| Duration::days(days as _));
+
+    challenges_ban::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user_id),
+        start: Set(now),
+        end: Set(duration.map(|duration| now + duration)),
+        action: Set(action),
+        creator: Set(creator),
+        reason: Set(reason),
+    }
+    .insert(db)
+    .await
+}
+
 pub async fn can_create(
     services: &Services,
     config: &Config,
@@ -679,3 +773,26 @@ pub enum UpdateSubtaskError {
     SubtaskNotFound,
     TaskNotFound,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reward_share_full() {
+        assert_eq!(reward_share(50, 0.0, 1.0), 50);
+        assert_eq!(reward_share(50, 1.0, 1.0), 0);
+    }
+
+    #[test]
+    fn reward_share_top_up() {
+        let steps = [0.0, 0.1, 1.0 / 3.0, 0.5, 0.9, 1.0];
+        let total = steps
+            .windows(2)
+            .map(|x| reward_share(7, x[0], x[1]))
+            .sum::<i64>();
+        assert_eq!(total, 7);
+        assert_eq!(reward_share(10, 0.0, 0.45), 5);
+        assert_eq!(reward_share(10, 0.45, 0.5), 0);
+    }
+}
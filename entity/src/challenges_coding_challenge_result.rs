@@ -4,11 +4,13 @@ use sea_orm::entity::prelude::*;
 
 use super::sea_orm_active_enums::ChallengesVerdict;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
 #[sea_orm(table_name = "challenges_coding_challenge_result")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
     pub submission_id: Uuid,
+    pub creation_timestamp: DateTime,
     pub verdict: ChallengesVerdict,
     #[sea_orm(column_type = "Text", nullable)]
     pub reason: Option<String>,
@@ -22,10 +24,28 @@ pub struct Model {
     pub run_stderr: Option<String>,
     pub run_time: Option<i32>,
     pub run_memory: Option<i32>,
+    #[sea_orm(column_type = "Double")]
+    pub score: f64,
+    pub evaluator_id: Option<Uuid>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub failed_seed: Option<String>,
+    pub failed_index: Option<i32>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub failed_input: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub failed_expected_output: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::challenges_coding_challenge_evaluators::Entity",
+        from = "Column::EvaluatorId",
+        to = "super::challenges_coding_challenge_evaluators::Column::Id",
+        on_update = "NoAction",
+        on_delete = "SetNull"
+    )]
+    ChallengesCodingChallengeEvaluators,
     #[sea_orm(
         belongs_to = "super::challenges_coding_challenge_submissions::Entity",
         from = "Column::SubmissionId",
@@ -34,6 +54,14 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     ChallengesCodingChallengeSubmissions,
+    #[sea_orm(has_many = "super::challenges_coding_challenge_test_result::Entity")]
+    ChallengesCodingChallengeTestResult,
+}
+
+impl Related<super::challenges_coding_challenge_evaluators::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesCodingChallengeEvaluators.def()
+    }
 }
 
 impl Related<super::challenges_coding_challenge_submissions::Entity> for Entity {
@@ -42,4 +70,10 @@ impl Related<super::challenges_coding_challenge_submissions::Entity> for Entity
     }
 }
 
+impl Related<super::challenges_coding_challenge_test_result::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesCodingChallengeTestResult.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -14,12 +14,20 @@ pub struct Model {
     pub environment: String,
     #[sea_orm(column_type = "Text")]
     pub code: String,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub hash: Option<String>,
+    pub hearts: i32,
+    pub shared: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::challenges_coding_challenge_judge_jobs::Entity")]
+    ChallengesCodingChallengeJudgeJobs,
     #[sea_orm(has_many = "super::challenges_coding_challenge_result::Entity")]
     ChallengesCodingChallengeResult,
+    #[sea_orm(has_many = "super::challenges_coding_challenge_submission_files::Entity")]
+    ChallengesCodingChallengeSubmissionFiles,
     #[sea_orm(
         belongs_to = "super::challenges_coding_challenges::Entity",
         from = "Column::SubtaskId",
@@ -30,12 +38,24 @@ pub enum Relation {
     ChallengesCodingChallenges,
 }
 
+impl Related<super::challenges_coding_challenge_judge_jobs::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesCodingChallengeJudgeJobs.def()
+    }
+}
+
 impl Related<super::challenges_coding_challenge_result::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::ChallengesCodingChallengeResult.def()
     }
 }
 
+impl Related<super::challenges_coding_challenge_submission_files::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesCodingChallengeSubmissionFiles.def()
+    }
+}
+
 impl Related<super::challenges_coding_challenges::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::ChallengesCodingChallenges.def()
@@ -2,6 +2,8 @@
 
 use sea_orm::entity::prelude::*;
 
+use super::sea_orm_active_enums::{ChallengesChecker, ChallengesScoringMode};
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
 #[sea_orm(table_name = "challenges_coding_challenges")]
 pub struct Model {
@@ -19,12 +21,26 @@ pub struct Model {
     pub solution_code: String,
     pub static_tests: i32,
     pub random_tests: i32,
+    pub scoring_mode: ChallengesScoringMode,
+    pub evaluator_id: Uuid,
+    pub checker: ChallengesChecker,
+    #[sea_orm(column_type = "Text")]
+    pub evaluator_environment: String,
+    pub starter_code: Json,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::challenges_coding_challenge_evaluators::Entity")]
+    ChallengesCodingChallengeEvaluators,
+    #[sea_orm(has_many = "super::challenges_coding_challenge_similarities::Entity")]
+    ChallengesCodingChallengeSimilarities,
     #[sea_orm(has_many = "super::challenges_coding_challenge_submissions::Entity")]
     ChallengesCodingChallengeSubmissions,
+    #[sea_orm(has_many = "super::challenges_coding_challenge_test_data::Entity")]
+    ChallengesCodingChallengeTestData,
+    #[sea_orm(has_many = "super::challenges_coding_challenge_verdict_cache::Entity")]
+    ChallengesCodingChallengeVerdictCache,
     #[sea_orm(
         belongs_to = "super::challenges_subtasks::Entity",
         from = "Column::SubtaskId",
@@ -35,12 +51,36 @@ pub enum Relation {
     ChallengesSubtasks,
 }
 
+impl Related<super::challenges_coding_challenge_evaluators::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesCodingChallengeEvaluators.def()
+    }
+}
+
+impl Related<super::challenges_coding_challenge_similarities::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesCodingChallengeSimilarities.def()
+    }
+}
+
 impl Related<super::challenges_coding_challenge_submissions::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::ChallengesCodingChallengeSubmissions.def()
     }
 }
 
+impl Related<super::challenges_coding_challenge_test_data::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesCodingChallengeTestData.def()
+    }
+}
+
+impl Related<super::challenges_coding_challenge_verdict_cache::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChallengesCodingChallengeVerdictCache.def()
+    }
+}
+
 impl Related<super::challenges_subtasks::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::ChallengesSubtasks.def()
@@ -5,8 +5,17 @@ pub mod prelude;
 pub mod challenges_ban;
 pub mod challenges_challenge_categories;
 pub mod challenges_challenges;
+pub mod challenges_coding_challenge_evaluators;
+pub mod challenges_coding_challenge_judge_jobs;
+pub mod challenges_coding_challenge_judge_workers;
 pub mod challenges_coding_challenge_result;
+pub mod challenges_coding_challenge_similarities;
+pub mod challenges_coding_challenge_similarity_scan;
+pub mod challenges_coding_challenge_submission_files;
 pub mod challenges_coding_challenge_submissions;
+pub mod challenges_coding_challenge_test_data;
+pub mod challenges_coding_challenge_test_result;
+pub mod challenges_coding_challenge_verdict_cache;
 pub mod challenges_coding_challenges;
 pub mod challenges_course_tasks;
 pub mod challenges_matching_attempts;
@@ -4,8 +4,17 @@ pub use super::{
     challenges_ban::Entity as ChallengesBan,
     challenges_challenge_categories::Entity as ChallengesChallengeCategories,
     challenges_challenges::Entity as ChallengesChallenges,
+    challenges_coding_challenge_evaluators::Entity as ChallengesCodingChallengeEvaluators,
+    challenges_coding_challenge_judge_jobs::Entity as ChallengesCodingChallengeJudgeJobs,
+    challenges_coding_challenge_judge_workers::Entity as ChallengesCodingChallengeJudgeWorkers,
     challenges_coding_challenge_result::Entity as ChallengesCodingChallengeResult,
+    challenges_coding_challenge_similarities::Entity as ChallengesCodingChallengeSimilarities,
+    challenges_coding_challenge_similarity_scan::Entity as ChallengesCodingChallengeSimilarityScan,
+    challenges_coding_challenge_submission_files::Entity as ChallengesCodingChallengeSubmissionFiles,
     challenges_coding_challenge_submissions::Entity as ChallengesCodingChallengeSubmissions,
+    challenges_coding_challenge_test_data::Entity as ChallengesCodingChallengeTestData,
+    challenges_coding_challenge_test_result::Entity as ChallengesCodingChallengeTestResult,
+    challenges_coding_challenge_verdict_cache::Entity as ChallengesCodingChallengeVerdictCache,
     challenges_coding_challenges::Entity as ChallengesCodingChallenges,
     challenges_course_tasks::Entity as ChallengesCourseTasks,
     challenges_matching_attempts::Entity as ChallengesMatchingAttempts,
@@ -26,6 +26,60 @@ pub enum ChallengesBanAction {
     Create,
     #[sea_orm(string_value = "report")]
     Report,
+    #[sea_orm(string_value = "submit")]
+    Submit,
+}
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    EnumIter,
+    DeriveActiveEnum,
+    Copy,
+    poem_openapi::Enum,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[oai(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "challenges_checker")]
+pub enum ChallengesChecker {
+    #[sea_orm(string_value = "evaluator")]
+    Evaluator,
+    #[sea_orm(string_value = "exact")]
+    Exact,
+    #[sea_orm(string_value = "float")]
+    Float,
+    #[sea_orm(string_value = "tokens")]
+    Tokens,
+}
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    EnumIter,
+    DeriveActiveEnum,
+    Copy,
+    poem_openapi::Enum,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[oai(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sea_orm(
+    rs_type = "String",
+    db_type = "Enum",
+    enum_name = "challenges_judge_priority"
+)]
+pub enum ChallengesJudgePriority {
+    #[sea_orm(string_value = "high")]
+    High,
+    #[sea_orm(string_value = "low")]
+    Low,
+    #[sea_orm(string_value = "normal")]
+    Normal,
 }
 #[derive(
     Debug,
@@ -95,6 +149,33 @@ pub enum ChallengesReportReason {
 )]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 #[oai(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sea_orm(
+    rs_type = "String",
+    db_type = "Enum",
+    enum_name = "challenges_scoring_mode"
+)]
+pub enum ChallengesScoringMode {
+    #[sea_orm(string_value = "all_or_nothing")]
+    AllOrNothing,
+    #[sea_orm(string_value = "fraction")]
+    Fraction,
+    #[sea_orm(string_value = "weighted")]
+    Weighted,
+}
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    EnumIter,
+    DeriveActiveEnum,
+    Copy,
+    poem_openapi::Enum,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[oai(rename_all = "SCREAMING_SNAKE_CASE")]
 #[sea_orm(
     rs_type = "String",
     db_type = "Enum",
@@ -128,6 +209,8 @@ pub enum ChallengesSubtaskType {
 pub enum ChallengesVerdict {
     #[sea_orm(string_value = "compilation_error")]
     CompilationError,
+    #[sea_orm(string_value = "internal_error")]
+    InternalError,
     #[sea_orm(string_value = "invalid_output_format")]
     InvalidOutputFormat,
     #[sea_orm(string_value = "memory_limit_exceeded")]
@@ -1,4 +1,6 @@
-use serde::Deserialize;
+use std::collections::HashMap;
+
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
 use url::Url;
 
 use super::Sentry;
@@ -9,6 +11,9 @@ pub struct ChallengesConfig {
     pub port: u16,
     pub server: String,
     pub sentry: Option<Sentry>,
+    /// The internal address the Prometheus metrics are served on. Metrics are
+    /// not served if this is not set.
+    pub metrics: Option<Metrics>,
     pub quizzes: Quizzes, // course tasks
     pub multiple_choice_questions: MultipleChoiceQuestions,
     pub questions: Questions,
@@ -16,6 +21,12 @@ pub struct ChallengesConfig {
     pub coding_challenges: CodingChallenges,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct Metrics {
+    pub host: String,
+    pub port: u16,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Quizzes {
     pub min_level: u32,
@@ -49,7 +60,134 @@ pub struct Matchings {
 pub struct CodingChallenges {
     pub sandkasten_url: Url,
     pub max_concurrency: usize,
+    pub test_concurrency: usize,
+    pub lease_duration: u64,
+    pub poll_interval: u64,
     pub timeout: u64,
+    /// The maximum number of submissions per user that may be waiting to be
+    /// judged at the same time.
+    pub max_pending_submissions: u64,
+    /// The maximum number of attempts to judge a submission before it
+    /// receives an internal error verdict.
+    pub max_judge_attempts: u32,
+    /// The delay before the second attempt to judge a submission in seconds.
+    /// The delay doubles with every further attempt.
+    pub retry_backoff: u64,
+    pub run_timeout: u64,
+    pub similarity_interval: u64,
+    pub similarity_threshold: f64,
+    /// The durations (in days) of consecutive bans for plagiarized
+    /// submissions. Further bans are permanent.
+    pub ban_days: Vec<u32>,
     pub hearts: u32,
     pub creator_coins: u32,
+    /// Multipliers for the time and memory limits of solutions written for
+    /// specific environments.
+    #[serde(default)]
+    pub limit_multipliers: HashMap<String, LimitMultipliers>,
+}
+
+impl CodingChallenges {
+    /// Return the limit multipliers for solutions written for the given
+    /// environment.
+    pub fn limit_multipliers(&self, environment: &str) -> LimitMultipliers {
+        self.limit_multipliers
+            .get(environment)
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LimitMultipliers {
+    #[serde(default = "one", deserialize_with = "multiplier")]
+    pub time: f64,
+    #[serde(default = "one", deserialize_with = "multiplier")]
+    pub memory: f64,
+}
+
+impl Default for LimitMultipliers {
+    fn default() -> Self {
+        Self {
+            time: 1.0,
+            memory: 1.0,
+        }
+    }
+}
+
+impl LimitMultipliers {
+    /// Return the time limit (in milliseconds) for a base time limit.
+    pub fn time_limit(&self, time_limit: u64) -> u64 {
+        (time_limit as f64 * self.time).round() as _
+    }
+
+    /// Return the memory limit (in megabytes) for a base memory limit.
+    pub fn memory_limit(&self, memory_limit: u64) -> u64 {
+        (memory_limit as f64 * self.memory).round() as _
+    }
+}
+
+fn one() -> f64 {
+    1.0
+}
+
+/// Deserialize a limit multiplier, which must be finite and positive.
+fn multiplier<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    let value = f64::deserialize(deserializer)?;
+    if !value.is_finite() || value <= 0.0 {
+        return Err(D::Error::custom(format!(
+            "limit multiplier must be finite and positive, got {value}"
+        )));
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_are_multiplied_and_rounded() {
+        let multipliers = LimitMultipliers {
+            time: 2.5,
+            memory: 0.5,
+        };
+        assert_eq!(multipliers.time_limit(1000), 2500);
+        assert_eq!(multipliers.time_limit(3), 8);
+        assert_eq!(multipliers.memory_limit(256), 128);
+        assert_eq!(multipliers.memory_limit(3), 2);
+    }
+
+    #[test]
+    fn default_multipliers_keep_limits() {
+        let multipliers = LimitMultipliers::default();
+        assert_eq!(multipliers.time_limit(1234), 1234);
+        assert_eq!(multipliers.memory_limit(64), 64);
+    }
+
+    #[test]
+    fn missing_multipliers_default_to_one() {
+        let multipliers = serde_json::from_str::<LimitMultipliers>(r#"{"time": 3}"#).unwrap();
+        assert_eq!(
+            multipliers,
+            LimitMultipliers {
+                time: 3.0,
+                memory: 1.0
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_multipliers_are_rejected() {
+        for json in [
+            r#"{"time": 0}"#,
+            r#"{"memory": -1.5}"#,
+            r#"{"time": 1e999}"#,
+        ] {
+            assert!(
+                serde_json::from_str::<LimitMultipliers>(json).is_err(),
+                "{json}"
+            );
+        }
+    }
 }
@@ -6,7 +6,7 @@ use url::Url;
 
 use self::challenges::ChallengesConfig;
 
-mod challenges;
+pub mod challenges;
 
 pub fn load() -> Result<Config, ConfigError> {
     load_config()
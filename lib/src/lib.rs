@@ -10,6 +10,7 @@ use crate::{jwt::JwtSecret, redis::RedisConnection};
 pub mod auth;
 pub mod config;
 pub mod jwt;
+pub mod metrics;
 pub mod redis;
 pub mod services;
 
@@ -0,0 +1,293 @@
+//! A minimal registry of metrics that are exposed in the Prometheus text
+//! format.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::Instant,
+};
+
+use poem::{Endpoint, IntoResponse, PathPattern, Request, Response};
+
+/// The default buckets for durations in seconds.
+pub const DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0,
+];
+
+pub static HTTP_REQUESTS: Counter = Counter::new(
+    "http_requests_total",
+    "Number of handled HTTP requests.",
+    &["method", "route", "status"],
+);
+
+pub static HTTP_REQUEST_DURATION: Histogram = Histogram::new(
+    "http_request_duration_seconds",
+    "Time spent handling HTTP requests.",
+    &["method", "route"],
+    DURATION_BUCKETS,
+);
+
+pub static SERVICE_REQUEST_DURATION: Histogram = Histogram::new(
+    "service_request_duration_seconds",
+    "Latency of requests to other microservices.",
+    &["service", "method"],
+    DURATION_BUCKETS,
+);
+
+pub static SERVICE_REQUEST_ERRORS: Counter = Counter::new(
+    "service_request_errors_total",
+    "Number of requests to other microservices that failed or returned an error status.",
+    &["service", "method"],
+);
+
+/// The metrics defined in this crate.
+pub static METRICS: &[&dyn Metric] = &[
+    &HTTP_REQUESTS,
+    &HTTP_REQUEST_DURATION,
+    &SERVICE_REQUEST_DURATION,
+    &SERVICE_REQUEST_ERRORS,
+];
+
+pub trait Metric: Sync {
+    /// Append the metric in the Prometheus text format to `out`.
+    fn render(&self, out: &mut String);
+}
+
+/// Render the given metrics in the Prometheus text format.
+pub fn render<'a>(metrics: impl IntoIterator<Item = &'a &'a dyn Metric>) -> String {
+    let mut out = String::new();
+    for metric in metrics {
+        metric.render(&mut out);
+    }
+    out
+}
+
+/// Middleware function that records the number and duration of HTTP requests
+/// per route. Use with [`poem::EndpointExt::around`].
+pub async fn track_http_requests<E: Endpoint>(ep: Arc<E>, req: Request) -> poem::Result<Response> {
+    let method = req.method().to_string();
+    let start = Instant::now();
+    let result = ep.call(req).await.map(IntoResponse::into_response);
+    let (status, route) = match &result {
+        Ok(res) => (res.status(), res.data::<PathPattern>()),
+        Err(err) => (err.status(), err.data::<PathPattern>()),
+    };
+    let route = route.map_or("unknown", |pattern| &pattern.0);
+    HTTP_REQUESTS.inc(&[&method, route, status.as_str()]);
+    HTTP_REQUEST_DURATION.observe(&[&method, route], start.elapsed().as_secs_f64());
+    result
+}
+
+/// A value that can only increase.
+pub struct Counter {
+    name: &'static str,
+    help: &'static str,
+    labels: &'static [&'static str],
+    values: Mutex<BTreeMap<Vec<String>, u64>>,
+}
+
+impl Counter {
+    pub const fn new(
+        name: &'static str,
+        help: &'static str,
+        labels: &'static [&'static str],
+    ) -> Self {
+        Self {
+            name,
+            help,
+            labels,
+            values: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Increment the counter for the given label values by one.
+    pub fn inc(&self, labels: &[&str]) {
+        self.inc_by(labels, 1);
+    }
+
+    /// Increment the counter for the given label values.
+    pub fn inc_by(&self, labels: &[&str], value: u64) {
+        *lock(&self.values).entry(label_values(labels)).or_default() += value;
+    }
+}
+
+impl Metric for Counter {
+    fn render(&self, out: &mut String) {
+        header(out, self.name, self.help, "counter");
+        for (values, value) in lock(&self.values).iter() {
+            sample(out, self.name, self.labels, values, None, value);
+        }
+    }
+}
+
+/// A value that can go up and down.
+pub struct Gauge {
+    name: &'static str,
+    help: &'static str,
+    labels: &'static [&'static str],
+    values: Mutex<BTreeMap<Vec<String>, f64>>,
+}
+
+impl Gauge {
+    pub const fn new(
+        name: &'static str,
+        help: &'static str,
+        labels: &'static [&'static str],
+    ) -> Self {
+        Self {
+            name,
+            help,
+            labels,
+            values: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Set the gauge for the given label values.
+    pub fn set(&self, labels: &[&str], value: f64) {
+        lock(&self.values).insert(label_values(labels), value);
+    }
+}
+
+impl Metric for Gauge {
+    fn render(&self, out: &mut String) {
+        header(out, self.name, self.help, "gauge");
+        for (values, value) in lock(&self.values).iter() {
+            sample(out, self.name, self.labels, values, None, value);
+        }
+    }
+}
+
+/// Counts observed values in configurable buckets.
+pub struct Histogram {
+    name: &'static str,
+    help: &'static str,
+    labels: &'static [&'static str],
+    /// The upper bounds of the buckets in increasing order.
+    buckets: &'static [f64],
+    values: Mutex<BTreeMap<Vec<String>, HistogramValue>>,
+}
+
+#[derive(Default)]
+struct HistogramValue {
+    /// The number of observations per bucket (not cumulative).
+    buckets: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    pub const fn new(
+        name: &'static str,
+        help: &'static str,
+        labels: &'static [&'static str],
+        buckets: &'static [f64],
+    ) -> Self {
+        Self {
+            name,
+            help,
+            labels,
+            buckets,
+            values: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Record an observation for the given label values.
+    pub fn observe(&self, labels: &[&str], value: f64) {
+        let mut values = lock(&self.values);
+        let entry = values.entry(label_values(labels)).or_default();
+        entry.buckets.resize(self.buckets.len(), 0);
+        if let Some(bucket) = self.buckets.iter().position(|&le| value <= le) {
+            entry.buckets[bucket] += 1;
+        }
+        entry.sum += value;
+        entry.count += 1;
+    }
+}
+
+impl Metric for Histogram {
+    fn render(&self, out: &mut String) {
+        header(out, self.name, self.help, "histogram");
+        let bucket_name = format!("{}_bucket", self.name);
+        for (values, value) in lock(&self.values).iter() {
+            let mut cumulative = 0;
+            for (le, count) in self.buckets.iter().zip(&value.buckets) {
+                cumulative += count;
+                let le = le.to_string();
+                sample(
+                    out,
+                    &bucket_name,
+                    self.labels,
+                    values,
+                    Some(&le),
+                    cumulative,
+                );
+            }
+            sample(
+                out,
+                &bucket_name,
+                self.labels,
+                values,
+                Some("+Inf"),
+                value.count,
+            );
+            sample(
+                out,
+                &format!("{}_sum", self.name),
+                self.labels,
+                values,
+                None,
+                value.sum,
+            );
+            sample(
+                out,
+                &format!("{}_count", self.name),
+                self.labels,
+                values,
+                None,
+                value.count,
+            );
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn label_values(labels: &[&str]) -> Vec<String> {
+    labels.iter().map(|&label| label.into()).collect()
+}
+
+fn header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+fn sample(
+    out: &mut String,
+    name: &str,
+    labels: &[&str],
+    values: &[String],
+    le: Option<&str>,
+    value: impl std::fmt::Display,
+) {
+    let labels = labels
+        .iter()
+        .zip(values)
+        .map(|(label, value)| (*label, value.as_str()))
+        .chain(le.map(|le| ("le", le)))
+        .map(|(label, value)| format!("{label}=\"{}\"", escape(value)))
+        .collect::<Vec<_>>();
+    let _ = match labels.is_empty() {
+        true => writeln!(out, "{name} {value}"),
+        false => writeln!(out, "{name}{{{}}} {value}", labels.join(",")),
+    };
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -1,13 +1,18 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use fnct::format::JsonFormatter;
-use reqwest::{Client, Method, RequestBuilder, StatusCode};
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode};
+use serde::Serialize;
 use thiserror::Error;
 use url::Url;
 
 use self::{auth::AuthService, shop::ShopService, skills::SkillsService};
 use crate::{
     jwt::{sign_jwt, InternalAuthToken, JwtSecret},
+    metrics::{SERVICE_REQUEST_DURATION, SERVICE_REQUEST_ERRORS},
     Cache, CacheError,
 };
 
@@ -77,7 +82,7 @@ impl Service {
         }
     }
 
-    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+    fn request(&self, method: Method, path: &str) -> ServiceRequest {
         let token = sign_jwt(
             InternalAuthToken {
                 aud: self.name.into(),
@@ -86,14 +91,57 @@ impl Service {
             self.jwt_config.ttl,
         )
         .expect("could not sign internal auth token");
-        Client::new()
+        let builder = Client::new()
             .request(
-                method,
+                method.clone(),
                 self.base_url
                     .join(&format!("_internal/{}", path.trim_start_matches('/')))
                     .expect("could not build url"),
             )
-            .bearer_auth(token)
+            .bearer_auth(token);
+        ServiceRequest {
+            service: self.name,
+            method,
+            builder,
+        }
+    }
+}
+
+/// A request to another microservice, which records the latency and errors of
+/// the request in the service metrics.
+struct ServiceRequest {
+    service: &'static str,
+    method: Method,
+    builder: RequestBuilder,
+}
+
+impl ServiceRequest {
+    fn json<T: Serialize + ?Sized>(self, json: &T) -> Self {
+        Self {
+            builder: self.builder.json(json),
+            ..self
+        }
+    }
+
+    fn query<T: Serialize + ?Sized>(self, query: &T) -> Self {
+        Self {
+            builder: self.builder.query(query),
+            ..self
+        }
+    }
+
+    async fn send(self) -> reqwest::Result<Response> {
+        let labels = [self.service, self.method.as_str()];
+        let start = Instant::now();
+        let result = self.builder.send().await;
+        SERVICE_REQUEST_DURATION.observe(&labels, start.elapsed().as_secs_f64());
+        if !result
+            .as_ref()
+            .is_ok_and(|response| response.status().is_success())
+        {
+            SERVICE_REQUEST_ERRORS.inc(&labels);
+        }
+        result
     }
 }
 
@@ -102,7 +150,7 @@ macro_rules! methods {
         paste::paste! {
             $(
                 #[allow(dead_code)]
-                fn $method(&self, path: &str) -> RequestBuilder {
+                fn $method(&self, path: &str) -> ServiceRequest {
                     self.request(Method::[< $method:upper >], path)
                 }
             )*
@@ -0,0 +1,32 @@
+use lib::metrics::{render, Counter, Histogram, Metric};
+
+static REQUESTS: Counter = Counter::new("requests_total", "Number of requests.", &["route"]);
+
+static DURATION: Histogram =
+    Histogram::new("duration_seconds", "Request duration.", &[], &[0.1, 1.0]);
+
+#[test]
+fn test_render() {
+    REQUESTS.inc(&["/a"]);
+    REQUESTS.inc_by(&["/b\"c"], 2);
+    REQUESTS.inc(&["/a"]);
+    DURATION.observe(&[], 0.05);
+    DURATION.observe(&[], 0.5);
+    DURATION.observe(&[], 5.0);
+
+    let metrics: &[&dyn Metric] = &[&REQUESTS, &DURATION];
+    assert_eq!(
+        render(metrics),
+        "# HELP requests_total Number of requests.\n\
+         # TYPE requests_total counter\n\
+         requests_total{route=\"/a\"} 2\n\
+         requests_total{route=\"/b\\\"c\"} 2\n\
+         # HELP duration_seconds Request duration.\n\
+         # TYPE duration_seconds histogram\n\
+         duration_seconds_bucket{le=\"0.1\"} 1\n\
+         duration_seconds_bucket{le=\"1\"} 2\n\
+         duration_seconds_bucket{le=\"+Inf\"} 3\n\
+         duration_seconds_sum 5.55\n\
+         duration_seconds_count 3\n"
+    );
+}
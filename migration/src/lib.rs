@@ -35,6 +35,23 @@ mod m20230815_162457_remove_subtask_fee;
 mod m20230815_203544_remove_subtask_unlocked;
 mod m20230816_173651_retire_subtasks;
 mod m20231014_142202_category_creation_timestamp;
+mod m20241021_153012_cc_test_results;
+mod m20241022_091204_cc_judge_queue;
+mod m20241023_104511_cc_rejudge;
+mod m20241024_140327_cc_scoring;
+mod m20241025_093518_cc_evaluator_revisions;
+mod m20241026_101204_cc_submission_files;
+mod m20241027_142250_cc_test_data;
+mod m20241028_160941_cc_checkers;
+mod m20241029_113027_cc_evaluator_environment;
+mod m20241030_084216_cc_similarities;
+mod m20241031_101532_cc_verdict_cache;
+mod m20241101_094410_cc_judge_priority;
+mod m20241102_130517_cc_internal_error;
+mod m20241103_091427_cc_failed_test;
+mod m20241104_102318_cc_starter_code;
+mod m20241105_143906_cc_shared_solutions;
+mod m20241106_081532_cc_similarity_scan;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
@@ -70,6 +87,23 @@ impl MigratorTrait for Migrator {
             Box::new(m20230815_203544_remove_subtask_unlocked::Migration),
             Box::new(m20230816_173651_retire_subtasks::Migration),
             Box::new(m20231014_142202_category_creation_timestamp::Migration),
+            Box::new(m20241021_153012_cc_test_results::Migration),
+            Box::new(m20241022_091204_cc_judge_queue::Migration),
+            Box::new(m20241023_104511_cc_rejudge::Migration),
+            Box::new(m20241024_140327_cc_scoring::Migration),
+            Box::new(m20241025_093518_cc_evaluator_revisions::Migration),
+            Box::new(m20241026_101204_cc_submission_files::Migration),
+            Box::new(m20241027_142250_cc_test_data::Migration),
+            Box::new(m20241028_160941_cc_checkers::Migration),
+            Box::new(m20241029_113027_cc_evaluator_environment::Migration),
+            Box::new(m20241030_084216_cc_similarities::Migration),
+            Box::new(m20241031_101532_cc_verdict_cache::Migration),
+            Box::new(m20241101_094410_cc_judge_priority::Migration),
+            Box::new(m20241102_130517_cc_internal_error::Migration),
+            Box::new(m20241103_091427_cc_failed_test::Migration),
+            Box::new(m20241104_102318_cc_starter_code::Migration),
+            Box::new(m20241105_143906_cc_shared_solutions::Migration),
+            Box::new(m20241106_081532_cc_similarity_scan::Migration),
         ]
     }
 }
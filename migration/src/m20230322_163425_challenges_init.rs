@@ -320,6 +320,11 @@ pub enum CodingChallenge {
     SolutionCode,
     StaticTests,
     RandomTests,
+    ScoringMode,
+    EvaluatorId,
+    Checker,
+    EvaluatorEnvironment,
+    StarterCode,
 }
 
 #[derive(Iden)]
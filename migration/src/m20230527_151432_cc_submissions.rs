@@ -113,13 +113,18 @@ pub enum Submission {
     CreationTimestamp,
     Environment,
     Code,
+    Hash,
+    Hearts,
+    Shared,
 }
 
 #[derive(Iden)]
-enum SubmissionResult {
+pub enum SubmissionResult {
     #[iden = "challenges_coding_challenge_result"]
     Table,
+    Id,
     SubmissionId,
+    CreationTimestamp,
     Verdict,
     Reason,
     BuildStatus,
@@ -130,6 +135,12 @@ enum SubmissionResult {
     RunStderr,
     RunTime,
     RunMemory,
+    Score,
+    EvaluatorId,
+    FailedSeed,
+    FailedIndex,
+    FailedInput,
+    FailedExpectedOutput,
 }
 
 #[derive(Iden)]
@@ -145,4 +156,5 @@ pub enum Verdict {
     CompilationError,
     RuntimeError,
     PreCheckFailed,
+    InternalError,
 }
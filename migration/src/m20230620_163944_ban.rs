@@ -62,9 +62,10 @@ pub enum Ban {
 }
 
 #[derive(Iden)]
-enum BanAction {
+pub enum BanAction {
     #[iden = "challenges_ban_action"]
     Type,
     Create,
     Report,
+    Submit,
 }
@@ -0,0 +1,63 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230527_151432_cc_submissions::{Submission, Verdict};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TestResult::Table)
+                    .col(ColumnDef::new(TestResult::SubmissionId).uuid().not_null())
+                    .col(ColumnDef::new(TestResult::Index).integer().not_null())
+                    .col(ColumnDef::new(TestResult::Seed).text().not_null())
+                    .col(
+                        ColumnDef::new(TestResult::Verdict)
+                            .custom(Verdict::Type)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(TestResult::Reason).text())
+                    .col(ColumnDef::new(TestResult::RunStatus).integer())
+                    .col(ColumnDef::new(TestResult::RunTime).integer())
+                    .col(ColumnDef::new(TestResult::RunMemory).integer())
+                    .primary_key(
+                        Index::create()
+                            .col(TestResult::SubmissionId)
+                            .col(TestResult::Index),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(TestResult::Table, TestResult::SubmissionId)
+                            .to(Submission::Table, Submission::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TestResult::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum TestResult {
+    #[iden = "challenges_coding_challenge_test_result"]
+    Table,
+    ResultId,
+    SubmissionId,
+    Index,
+    Seed,
+    Verdict,
+    Reason,
+    RunStatus,
+    RunTime,
+    RunMemory,
+}
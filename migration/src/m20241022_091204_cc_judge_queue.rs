@@ -0,0 +1,124 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230527_151432_cc_submissions::{Submission, SubmissionResult};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(JudgeWorker::Table)
+                    .col(
+                        ColumnDef::new(JudgeWorker::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(JudgeWorker::Concurrency)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(JudgeWorker::HeartbeatTimestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(JudgeJob::Table)
+                    .col(
+                        ColumnDef::new(JudgeJob::SubmissionId)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(JudgeJob::CreationTimestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(JudgeJob::Worker).uuid().null())
+                    .col(ColumnDef::new(JudgeJob::LeaseExpiration).timestamp().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(JudgeJob::Table, JudgeJob::SubmissionId)
+                            .to(Submission::Table, Submission::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        // enqueue all submissions that have not been judged yet
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(JudgeJob::Table)
+                    .columns([JudgeJob::SubmissionId, JudgeJob::CreationTimestamp])
+                    .select_from(
+                        Query::select()
+                            .column((Submission::Table, Submission::Id))
+                            .column((Submission::Table, Submission::CreationTimestamp))
+                            .from(Submission::Table)
+                            .left_join(
+                                SubmissionResult::Table,
+                                Expr::col((
+                                    SubmissionResult::Table,
+                                    SubmissionResult::SubmissionId,
+                                ))
+                                .equals((Submission::Table, Submission::Id)),
+                            )
+                            .and_where(
+                                Expr::col((
+                                    SubmissionResult::Table,
+                                    SubmissionResult::SubmissionId,
+                                ))
+                                .is_null(),
+                            )
+                            .to_owned(),
+                    )
+                    .map_err(|err| DbErr::Custom(err.to_string()))?
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(JudgeJob::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(JudgeWorker::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum JudgeJob {
+    #[iden = "challenges_coding_challenge_judge_jobs"]
+    Table,
+    SubmissionId,
+    CreationTimestamp,
+    Worker,
+    LeaseExpiration,
+    Rejudge,
+    Priority,
+    Attempts,
+}
+
+#[derive(Iden)]
+pub enum JudgeWorker {
+    #[iden = "challenges_coding_challenge_judge_workers"]
+    Table,
+    Id,
+    Concurrency,
+    HeartbeatTimestamp,
+}
@@ -0,0 +1,180 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20230527_151432_cc_submissions::{Submission, SubmissionResult},
+    m20241021_153012_cc_test_results::TestResult,
+    m20241022_091204_cc_judge_queue::JudgeJob,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let conn = manager.get_connection();
+
+        // allow multiple results per submission
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubmissionResult::Table)
+                    .add_column(ColumnDef::new(SubmissionResult::Id).uuid().null())
+                    .add_column(
+                        ColumnDef::new(SubmissionResult::CreationTimestamp)
+                            .timestamp()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        conn.execute_unprepared(
+            "UPDATE challenges_coding_challenge_result r SET id = r.submission_id, \
+             creation_timestamp = s.creation_timestamp FROM \
+             challenges_coding_challenge_submissions s WHERE s.id = r.submission_id",
+        )
+        .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubmissionResult::Table)
+                    .modify_column(ColumnDef::new(SubmissionResult::Id).not_null())
+                    .modify_column(ColumnDef::new(SubmissionResult::CreationTimestamp).not_null())
+                    .to_owned(),
+            )
+            .await?;
+        conn.execute_unprepared(
+            "ALTER TABLE challenges_coding_challenge_result DROP CONSTRAINT \
+             challenges_coding_challenge_result_pkey, ADD PRIMARY KEY (id)",
+        )
+        .await?;
+
+        // test results belong to a result instead of a submission
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TestResult::Table)
+                    .add_column(ColumnDef::new(TestResult::ResultId).uuid().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(TestResult::Table)
+                    .value(TestResult::ResultId, Expr::col(TestResult::SubmissionId))
+                    .to_owned(),
+            )
+            .await?;
+        conn.execute_unprepared(
+            "ALTER TABLE challenges_coding_challenge_test_result DROP CONSTRAINT \
+             challenges_coding_challenge_test_result_pkey, ADD PRIMARY KEY (result_id, index)",
+        )
+        .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TestResult::Table)
+                    .drop_column(TestResult::SubmissionId)
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .from_tbl(TestResult::Table)
+                            .from_col(TestResult::ResultId)
+                            .to_tbl(SubmissionResult::Table)
+                            .to_col(SubmissionResult::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(JudgeJob::Table)
+                    .add_column(
+                        ColumnDef::new(JudgeJob::Rejudge)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let conn = manager.get_connection();
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(JudgeJob::Table)
+                    .drop_column(JudgeJob::Rejudge)
+                    .to_owned(),
+            )
+            .await?;
+
+        // keep only the latest result of each submission
+        conn.execute_unprepared(
+            "DELETE FROM challenges_coding_challenge_result r USING \
+             challenges_coding_challenge_result s WHERE r.submission_id = s.submission_id AND \
+             (r.creation_timestamp, r.id) < (s.creation_timestamp, s.id)",
+        )
+        .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TestResult::Table)
+                    .add_column(ColumnDef::new(TestResult::SubmissionId).uuid().null())
+                    .to_owned(),
+            )
+            .await?;
+        conn.execute_unprepared(
+            "UPDATE challenges_coding_challenge_test_result t SET submission_id = \
+             r.submission_id FROM challenges_coding_challenge_result r WHERE r.id = t.result_id",
+        )
+        .await?;
+        conn.execute_unprepared(
+            "ALTER TABLE challenges_coding_challenge_test_result DROP CONSTRAINT \
+             challenges_coding_challenge_test_result_pkey, ADD PRIMARY KEY (submission_id, index)",
+        )
+        .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TestResult::Table)
+                    .drop_column(TestResult::ResultId)
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .from_tbl(TestResult::Table)
+                            .from_col(TestResult::SubmissionId)
+                            .to_tbl(Submission::Table)
+                            .to_col(Submission::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        conn.execute_unprepared(
+            "ALTER TABLE challenges_coding_challenge_result DROP CONSTRAINT \
+             challenges_coding_challenge_result_pkey, ADD PRIMARY KEY (submission_id)",
+        )
+        .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubmissionResult::Table)
+                    .drop_column(SubmissionResult::Id)
+                    .drop_column(SubmissionResult::CreationTimestamp)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+use sea_orm_migration::{prelude::*, sea_query::extension::postgres::Type};
+
+use crate::{
+    m20230322_163425_challenges_init::CodingChallenge,
+    m20230527_151432_cc_submissions::{SubmissionResult, Verdict},
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(ScoringMode::Type)
+                    .values([
+                        ScoringMode::AllOrNothing,
+                        ScoringMode::Fraction,
+                        ScoringMode::Weighted,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CodingChallenge::Table)
+                    .add_column(
+                        ColumnDef::new(CodingChallenge::ScoringMode)
+                            .custom(ScoringMode::Type)
+                            .default(ScoringMode::AllOrNothing.to_string())
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubmissionResult::Table)
+                    .add_column(
+                        ColumnDef::new(SubmissionResult::Score)
+                            .double()
+                            .default(0.0)
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(SubmissionResult::Table)
+                    .value(SubmissionResult::Score, 1.0)
+                    .and_where(
+                        Expr::col(SubmissionResult::Verdict)
+                            .eq(Expr::val(Verdict::Ok.to_string()).as_enum(Verdict::Type)),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubmissionResult::Table)
+                    .drop_column(SubmissionResult::Score)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CodingChallenge::Table)
+                    .drop_column(CodingChallenge::ScoringMode)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(ScoringMode::Type).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum ScoringMode {
+    #[iden = "challenges_scoring_mode"]
+    Type,
+    AllOrNothing,
+    Fraction,
+    Weighted,
+}
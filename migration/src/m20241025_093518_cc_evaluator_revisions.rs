@@ -0,0 +1,168 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20230322_163425_challenges_init::{CodingChallenge, Subtask},
+    m20230527_151432_cc_submissions::SubmissionResult,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(EvaluatorRevision::Table)
+                    .col(
+                        ColumnDef::new(EvaluatorRevision::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(EvaluatorRevision::ChallengeId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(EvaluatorRevision::Creator).uuid().not_null())
+                    .col(
+                        ColumnDef::new(EvaluatorRevision::CreationTimestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(EvaluatorRevision::Evaluator)
+                            .text()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(EvaluatorRevision::Table, EvaluatorRevision::ChallengeId)
+                            .to(CodingChallenge::Table, CodingChallenge::SubtaskId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // the current evaluator of each coding challenge becomes its first revision
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(EvaluatorRevision::Table)
+                    .columns([
+                        EvaluatorRevision::Id,
+                        EvaluatorRevision::ChallengeId,
+                        EvaluatorRevision::Creator,
+                        EvaluatorRevision::CreationTimestamp,
+                        EvaluatorRevision::Evaluator,
+                    ])
+                    .select_from(
+                        Query::select()
+                            .column((CodingChallenge::Table, CodingChallenge::SubtaskId))
+                            .column((CodingChallenge::Table, CodingChallenge::SubtaskId))
+                            .column((Subtask::Table, Subtask::Creator))
+                            .column((Subtask::Table, Subtask::CreationTimestamp))
+                            .column((CodingChallenge::Table, CodingChallenge::Evaluator))
+                            .from(CodingChallenge::Table)
+                            .inner_join(
+                                Subtask::Table,
+                                Expr::col((Subtask::Table, Subtask::Id))
+                                    .equals((CodingChallenge::Table, CodingChallenge::SubtaskId)),
+                            )
+                            .to_owned(),
+                    )
+                    .map_err(|err| DbErr::Migration(err.to_string()))?
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CodingChallenge::Table)
+                    .add_column(ColumnDef::new(CodingChallenge::EvaluatorId).uuid().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(CodingChallenge::Table)
+                    .value(
+                        CodingChallenge::EvaluatorId,
+                        Expr::col(CodingChallenge::SubtaskId),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CodingChallenge::Table)
+                    .modify_column(ColumnDef::new(CodingChallenge::EvaluatorId).not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // the revision that produced results judged before this migration is unknown
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubmissionResult::Table)
+                    .add_column(ColumnDef::new(SubmissionResult::EvaluatorId).uuid().null())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .from_tbl(SubmissionResult::Table)
+                            .from_col(SubmissionResult::EvaluatorId)
+                            .to_tbl(EvaluatorRevision::Table)
+                            .to_col(EvaluatorRevision::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubmissionResult::Table)
+                    .drop_column(SubmissionResult::EvaluatorId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CodingChallenge::Table)
+                    .drop_column(CodingChallenge::EvaluatorId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(EvaluatorRevision::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum EvaluatorRevision {
+    #[iden = "challenges_coding_challenge_evaluators"]
+    Table,
+    Id,
+    ChallengeId,
+    Creator,
+    CreationTimestamp,
+    Evaluator,
+    Environment,
+}
@@ -0,0 +1,53 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230322_163425_challenges_init::CodingChallenge;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TestData::Table)
+                    .col(ColumnDef::new(TestData::Id).uuid().not_null().primary_key())
+                    .col(ColumnDef::new(TestData::ChallengeId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(TestData::CreationTimestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(TestData::Input).text().not_null())
+                    .col(ColumnDef::new(TestData::Output).text())
+                    .col(ColumnDef::new(TestData::Data).json_binary())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(TestData::Table, TestData::ChallengeId)
+                            .to(CodingChallenge::Table, CodingChallenge::SubtaskId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TestData::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum TestData {
+    #[iden = "challenges_coding_challenge_test_data"]
+    Table,
+    Id,
+    ChallengeId,
+    CreationTimestamp,
+    Input,
+    Output,
+    Data,
+}
@@ -0,0 +1,66 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20230322_163425_challenges_init::CodingChallenge,
+    m20241025_093518_cc_evaluator_revisions::EvaluatorRevision,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CodingChallenge::Table)
+                    .add_column(
+                        ColumnDef::new(CodingChallenge::EvaluatorEnvironment)
+                            .text()
+                            .default("python")
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(EvaluatorRevision::Table)
+                    .add_column(
+                        ColumnDef::new(EvaluatorRevision::Environment)
+                            .text()
+                            .default("python")
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(EvaluatorRevision::Table)
+                    .drop_column(EvaluatorRevision::Environment)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CodingChallenge::Table)
+                    .drop_column(CodingChallenge::EvaluatorEnvironment)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
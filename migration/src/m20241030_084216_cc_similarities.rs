@@ -0,0 +1,130 @@
+use sea_orm_migration::{prelude::*, sea_query::extension::postgres::Type};
+
+use crate::{
+    m20230322_163425_challenges_init::CodingChallenge, m20230527_151432_cc_submissions::Submission,
+    m20230620_163944_ban::BanAction,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let sql = Type::alter()
+            .name(BanAction::Type)
+            .add_value(BanAction::Submit)
+            .to_string(PostgresQueryBuilder)
+            .replace("ADD VALUE", "ADD VALUE IF NOT EXISTS");
+        manager.get_connection().execute_unprepared(&sql).await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(SubmissionSimilarity::Table)
+                    .col(
+                        ColumnDef::new(SubmissionSimilarity::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(SubmissionSimilarity::ChallengeId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SubmissionSimilarity::SubmissionA)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SubmissionSimilarity::SubmissionB)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SubmissionSimilarity::UserA)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SubmissionSimilarity::UserB)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SubmissionSimilarity::Similarity)
+                            .double()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SubmissionSimilarity::CreationTimestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SubmissionSimilarity::Resolved)
+                            .boolean()
+                            .default(false)
+                            .not_null(),
+                    )
+                    .index(
+                        Index::create()
+                            .unique()
+                            .col(SubmissionSimilarity::SubmissionA)
+                            .col(SubmissionSimilarity::SubmissionB),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(
+                                SubmissionSimilarity::Table,
+                                SubmissionSimilarity::ChallengeId,
+                            )
+                            .to(CodingChallenge::Table, CodingChallenge::SubtaskId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(
+                                SubmissionSimilarity::Table,
+                                SubmissionSimilarity::SubmissionA,
+                            )
+                            .to(Submission::Table, Submission::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(
+                                SubmissionSimilarity::Table,
+                                SubmissionSimilarity::SubmissionB,
+                            )
+                            .to(Submission::Table, Submission::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SubmissionSimilarity::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum SubmissionSimilarity {
+    #[iden = "challenges_coding_challenge_similarities"]
+    Table,
+    Id,
+    ChallengeId,
+    SubmissionA,
+    SubmissionB,
+    UserA,
+    UserB,
+    Similarity,
+    CreationTimestamp,
+    Resolved,
+}
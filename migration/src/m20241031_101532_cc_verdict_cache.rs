@@ -0,0 +1,84 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20230322_163425_challenges_init::CodingChallenge, m20230527_151432_cc_submissions::Submission,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Submission::Table)
+                    .add_column(ColumnDef::new(Submission::Hash).text().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(VerdictCache::Table)
+                    .col(ColumnDef::new(VerdictCache::Hash).text().not_null())
+                    .col(ColumnDef::new(VerdictCache::Seed).text().not_null())
+                    .col(ColumnDef::new(VerdictCache::ChallengeId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(VerdictCache::CreationTimestamp)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(VerdictCache::Result)
+                            .json_binary()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(VerdictCache::Hash)
+                            .col(VerdictCache::Seed),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(VerdictCache::Table, VerdictCache::ChallengeId)
+                            .to(CodingChallenge::Table, CodingChallenge::SubtaskId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(VerdictCache::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Submission::Table)
+                    .drop_column(Submission::Hash)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum VerdictCache {
+    #[iden = "challenges_coding_challenge_verdict_cache"]
+    Table,
+    Hash,
+    Seed,
+    ChallengeId,
+    CreationTimestamp,
+    Result,
+}
@@ -0,0 +1,79 @@
+use sea_orm_migration::{prelude::*, sea_query::extension::postgres::Type};
+
+use crate::m20241022_091204_cc_judge_queue::JudgeJob;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(JudgePriority::Type)
+                    .values([
+                        JudgePriority::High,
+                        JudgePriority::Normal,
+                        JudgePriority::Low,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(JudgeJob::Table)
+                    .add_column(
+                        ColumnDef::new(JudgeJob::Priority)
+                            .custom(JudgePriority::Type)
+                            .default(JudgePriority::Normal.to_string())
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(JudgeJob::Table)
+                    .value(
+                        JudgeJob::Priority,
+                        Expr::val(JudgePriority::Low.to_string()).as_enum(JudgePriority::Type),
+                    )
+                    .and_where(Expr::col(JudgeJob::Rejudge).eq(true))
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(JudgeJob::Table)
+                    .drop_column(JudgeJob::Priority)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(JudgePriority::Type).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum JudgePriority {
+    #[iden = "challenges_judge_priority"]
+    Type,
+    High,
+    Normal,
+    Low,
+}
@@ -0,0 +1,45 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230527_151432_cc_submissions::SubmissionResult;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubmissionResult::Table)
+                    .add_column(ColumnDef::new(SubmissionResult::FailedSeed).text().null())
+                    .add_column(
+                        ColumnDef::new(SubmissionResult::FailedIndex)
+                            .integer()
+                            .null(),
+                    )
+                    .add_column(ColumnDef::new(SubmissionResult::FailedInput).text().null())
+                    .add_column(
+                        ColumnDef::new(SubmissionResult::FailedExpectedOutput)
+                            .text()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubmissionResult::Table)
+                    .drop_column(SubmissionResult::FailedSeed)
+                    .drop_column(SubmissionResult::FailedIndex)
+                    .drop_column(SubmissionResult::FailedInput)
+                    .drop_column(SubmissionResult::FailedExpectedOutput)
+                    .to_owned(),
+            )
+            .await
+    }
+}
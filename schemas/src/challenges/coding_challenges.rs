@@ -1,18 +1,25 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use entity::{
-    challenges_coding_challenge_result, challenges_coding_challenge_submissions,
-    challenges_coding_challenges, sea_orm_active_enums::ChallengesVerdict,
+    challenges_coding_challenge_evaluators, challenges_coding_challenge_result,
+    challenges_coding_challenge_similarities, challenges_coding_challenge_submissions,
+    challenges_coding_challenge_test_data, challenges_coding_challenge_test_result,
+    challenges_coding_challenges,
+    sea_orm_active_enums::{ChallengesChecker, ChallengesScoringMode, ChallengesVerdict},
 };
+use lib::config::challenges::{self, CodingChallenges};
 use poem_ext::patch_value::PatchValue;
 use poem_openapi::{
     types::{ParseFromJSON, ToJSON, Type},
-    Object,
+    Enum, Object, Union,
 };
 use sandkasten_client::schemas::{
     configuration::PublicConfig,
-    programs::{ResourceUsage, RunResult},
+    programs::{File, ResourceUsage, RunResult},
 };
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use uuid::Uuid;
 
 use super::subtasks::{CreateSubtaskRequest, Subtask, UpdateSubtaskRequest};
@@ -41,6 +48,10 @@ pub struct CodingChallengeSummary {
     pub static_tests: u8,
     /// The number of random tests to run for submission evaluation.
     pub random_tests: u8,
+    /// How submissions are scored.
+    pub scoring_mode: ChallengesScoringMode,
+    /// How the output of solutions is checked.
+    pub checker: ChallengesChecker,
 }
 
 #[derive(Debug, Clone, Object)]
@@ -57,6 +68,26 @@ pub struct CodingChallenge {
     pub static_tests: u8,
     /// The number of random tests to run for submission evaluation.
     pub random_tests: u8,
+    /// How submissions are scored.
+    pub scoring_mode: ChallengesScoringMode,
+    /// How the output of solutions is checked.
+    pub checker: ChallengesChecker,
+    /// The environment the evaluator is written for.
+    pub evaluator_environment: String,
+    /// The limits for solutions written for specific environments, capped at
+    /// the maximum limits of the executor. Solutions for other environments
+    /// use `time_limit` and `memory_limit`.
+    pub environment_limits: HashMap<String, Limits>,
+    /// The code users start with for specific environments.
+    pub starter_code: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, Object)]
+pub struct Limits {
+    /// The number of milliseconds the solution may run.
+    pub time_limit: u64,
+    /// The number of megabytes of memory the solution may use.
+    pub memory_limit: u64,
 }
 
 #[derive(Debug, Clone, Object, Serialize, Deserialize)]
@@ -93,18 +124,48 @@ pub struct CreateCodingChallengeRequest {
         validator(minimum(value = "1"), maximum(value = "20"))
     )]
     pub random_tests: u8,
+    /// How submissions are scored. In the `FRACTION` and `WEIGHTED` modes all
+    /// tests are run and users receive the share of the rewards that
+    /// corresponds to their score. In the `WEIGHTED` mode the evaluator
+    /// determines the weight of each test.
+    #[oai(default = "scoring_mode_default")]
+    pub scoring_mode: ChallengesScoringMode,
+    /// How the output of solutions is checked. The `EXACT`, `TOKENS` and
+    /// `FLOAT` checkers compare the output to the expected output provided
+    /// by the evaluator or, if there is none, to the output of the sample
+    /// solution. The `EVALUATOR` checker uses the evaluator's `check`
+    /// function.
+    #[oai(default = "checker_default")]
+    pub checker: ChallengesChecker,
     /// The program used to generate test cases and evaluate solutions
     #[oai(validator(max_length = 65536))]
     pub evaluator: String,
+    /// The environment the evaluator is written for.
+    #[oai(default = "evaluator_environment_default")]
+    pub evaluator_environment: String,
     /// The environment to run the solution in.
     pub solution_environment: String,
     /// The solution code
     #[oai(validator(max_length = 65536))]
     pub solution_code: String,
+    /// The code users start with for specific environments. The keys are
+    /// environment ids. Each starter code must be accepted by the evaluator's
+    /// `prepare` function.
+    #[oai(default, validator(max_properties = 32, max_length = 65536))]
+    pub starter_code: HashMap<String, String>,
 }
 fn tests_default() -> u8 {
     10
 }
+fn scoring_mode_default() -> ChallengesScoringMode {
+    ChallengesScoringMode::AllOrNothing
+}
+fn checker_default() -> ChallengesChecker {
+    ChallengesChecker::Evaluator
+}
+fn evaluator_environment_default() -> String {
+    "python".into()
+}
 
 #[derive(Debug, Clone, Object)]
 pub struct UpdateCodingChallengeRequest {
@@ -125,14 +186,23 @@ pub struct UpdateCodingChallengeRequest {
     /// The number of random tests to run for submission evaluation.
     #[oai(validator(minimum(value = "1"), maximum(value = "20")))]
     pub random_tests: PatchValue<u8>,
+    /// How submissions are scored.
+    pub scoring_mode: PatchValue<ChallengesScoringMode>,
+    /// How the output of solutions is checked.
+    pub checker: PatchValue<ChallengesChecker>,
     /// The program used to generate test cases and evaluate solutions
     #[oai(validator(max_length = 65536))]
     pub evaluator: PatchValue<String>,
+    /// The environment the evaluator is written for.
+    pub evaluator_environment: PatchValue<String>,
     /// The environment to run the solution in.
     pub solution_environment: PatchValue<String>,
     /// The solution code
     #[oai(validator(max_length = 65536))]
     pub solution_code: PatchValue<String>,
+    /// The code users start with for specific environments.
+    #[oai(validator(max_properties = 32, max_length = 65536))]
+    pub starter_code: PatchValue<HashMap<String, String>>,
 }
 
 #[derive(Debug, Clone, Object)]
@@ -149,17 +219,392 @@ pub struct Submission {
     pub environment: String,
     /// The evaluation result of the submission.
     pub result: Option<CheckResult<RunSummary>>,
+    /// The score of the submission between `0` and `1`.
+    pub score: Option<f64>,
     /// The number of submissions in the judge's queue before this one.
     pub queue_position: Option<usize>,
+    /// The first test case the submission failed on.
+    pub failed_test: Option<FailedTest>,
+    /// Whether users who have solved the coding challenge can see the
+    /// submission.
+    pub shared: bool,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct FailedTest {
+    /// The position of the test case in the list of tests run for the
+    /// submission.
+    pub index: u32,
+    /// The seed that was used to generate the test case.
+    pub seed: String,
+    /// The input the program received via stdin. Only revealed for examples.
+    pub input: Option<String>,
+    /// The output the program should have produced on stdout. Only revealed
+    /// for examples.
+    pub expected_output: Option<String>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct TestResult {
+    /// The position of the test case in the list of tests run for the
+    /// submission.
+    pub index: u32,
+    /// The seed that was used to generate the test case.
+    pub seed: String,
+    /// The verdict of the test case.
+    pub verdict: ChallengesVerdict,
+    /// Additional information provided by the evaluator.
+    pub reason: Option<String>,
+    /// The exit code of the solution. Empty if the solution has not been run.
+    pub status: Option<i32>,
+    /// The amount of resources the solution used. Empty if the solution has
+    /// not been run.
+    pub resource_usage: Option<ResourceUsage>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct ShareSubmissionRequest {
+    /// Whether users who have solved the coding challenge can see the
+    /// submission.
+    pub shared: bool,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SharedSolution {
+    /// The unique identifier of the submission.
+    pub id: Uuid,
+    /// The creator of the submission.
+    pub creator: Uuid,
+    /// The creation timestamp of the submission.
+    pub creation_timestamp: DateTime<Utc>,
+    /// The environment of the submission.
+    pub environment: String,
+    /// The code of the submission.
+    pub code: String,
+    /// Additional files of the submission.
+    pub files: Vec<File>,
+    /// The maximum amount of time and memory the submission has used on a
+    /// single test case.
+    pub resource_usage: Option<ResourceUsage>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[oai(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SharedSolutionOrder {
+    /// Newest solutions first.
+    CreationTimestamp,
+    /// Fastest solutions first.
+    RunTime,
+    /// Solutions that use the least memory first.
+    RunMemory,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct RejudgeRequest {
+    /// Only rejudge submissions whose latest result has one of these verdicts.
+    pub verdicts: Option<Vec<ChallengesVerdict>>,
+    /// Only rejudge submissions that have been created at or after this
+    /// timestamp.
+    pub since: Option<DateTime<Utc>>,
+    /// Only rejudge submissions that have been created before this timestamp.
+    pub until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct RejudgeStatus {
+    /// The number of submissions that are waiting to be rejudged.
+    pub pending: u64,
+    /// The users who have solved the coding challenge but none of whose
+    /// submissions pass anymore.
+    pub no_longer_passing: Vec<Uuid>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct ReplayRequest {
+    /// The seed of the test case to run. Defaults to the seed of the first
+    /// test case the submission failed on.
+    pub seed: Option<String>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct ReplayResult {
+    /// The seed of the test case.
+    pub seed: String,
+    /// The input the program received via stdin.
+    pub input: String,
+    /// The result of running the submission on the test case.
+    pub result: CheckResult<RunResult>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct CodingChallengeStats {
+    #[oai(flatten)]
+    pub total: SubmissionStats,
+    /// The statistics of the submissions written for each environment.
+    pub environments: HashMap<String, SubmissionStats>,
+}
+
+#[derive(Debug, Clone, Default, Object)]
+pub struct SubmissionStats {
+    /// The number of submissions.
+    pub submissions: u64,
+    /// The number of users who have submitted a solution.
+    pub users: u64,
+    /// The number of submissions that have been judged.
+    pub judged: u64,
+    /// The number of submissions that have been accepted.
+    pub accepted: u64,
+    /// The share of judged submissions that have been accepted (between `0`
+    /// and `1`).
+    pub acceptance_rate: f64,
+    /// The number of users who have at least one accepted submission.
+    pub solved_by: u64,
+    /// The number of judged submissions for each verdict.
+    pub verdicts: Vec<VerdictCount>,
+    /// The median number of submissions users have needed until their first
+    /// accepted submission.
+    pub median_attempts: Option<f64>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct VerdictCount {
+    /// The verdict of the latest result of the submissions.
+    pub verdict: ChallengesVerdict,
+    /// The number of submissions with this verdict.
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct EvaluatorRevision {
+    /// The unique identifier of the evaluator revision.
+    pub id: Uuid,
+    /// The user who created the evaluator revision.
+    pub creator: Uuid,
+    /// The creation timestamp of the evaluator revision.
+    pub creation_timestamp: DateTime<Utc>,
+    /// The environment the evaluator is written for.
+    pub environment: String,
+    /// Whether this revision is the current evaluator of the coding challenge.
+    pub current: bool,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct EvaluatorDiff {
+    /// The revision the changes are based on. Empty if the diff starts from
+    /// an empty evaluator.
+    pub base: Option<Uuid>,
+    /// The revision containing the changes.
+    pub revision: Uuid,
+    /// The changes in unified diff format.
+    pub diff: String,
+}
+
+/// An update on the status of a submission.
+#[derive(Debug, Clone, Union)]
+#[oai(discriminator_name = "type", one_of)]
+pub enum SubmissionEvent {
+    /// The position of the submission in the judge's queue has changed.
+    #[oai(mapping = "queue_position")]
+    QueuePosition(SubmissionQueuePosition),
+    /// The judge has started to run a test of the submission.
+    #[oai(mapping = "progress")]
+    Progress(SubmissionProgress),
+    /// The submission has been judged. This is always the last event.
+    #[oai(mapping = "result")]
+    Result(CheckResult<RunSummary>),
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SubmissionQueuePosition {
+    /// The number of submissions in the judge's queue before this one. `0` if
+    /// the submission is currently being judged.
+    pub position: usize,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct SubmissionProgress {
+    /// The number of the test that is running, starting at `1`.
+    pub test: usize,
+    /// The total number of tests. The judge stops after the first failed test,
+    /// so not all of them may be run.
+    pub total: usize,
 }
 
 #[derive(Debug, Clone, Object)]
 pub struct SubmissionContent {
     /// The environment to run the solution in.
     pub environment: String,
-    /// The solution code.
+    /// The solution code. This is the main file, which represents the
+    /// entrypoint to the program.
     #[oai(validator(max_length = 65536))]
     pub code: String,
+    /// A list of additional source files.
+    #[oai(default, validator(max_items = 10))]
+    pub files: Vec<File>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct TestData {
+    /// The unique identifier of the test case.
+    pub id: Uuid,
+    /// The creation timestamp of the test case.
+    pub creation_timestamp: DateTime<Utc>,
+    /// The input that is passed to the solution via stdin.
+    pub input: String,
+    /// The expected output of the solution. If set, the output of the solution
+    /// is compared to it, ignoring differences in whitespace. Otherwise the
+    /// output is checked by the evaluator.
+    pub output: Option<String>,
+    /// The data that is passed to the evaluator when preparing the solution and
+    /// checking its output.
+    pub data: Option<Value>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct CreateTestDataRequest {
+    /// The input that is passed to the solution via stdin.
+    #[oai(validator(max_length = 65536))]
+    pub input: String,
+    /// The expected output of the solution. If set, the output of the solution
+    /// is compared to it, ignoring differences in whitespace. Otherwise the
+    /// output is checked by the evaluator.
+    #[oai(validator(max_length = 65536))]
+    pub output: Option<String>,
+    /// The data that is passed to the evaluator when preparing the solution and
+    /// checking its output.
+    pub data: Option<Value>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct UpdateTestDataRequest {
+    /// The input that is passed to the solution via stdin.
+    #[oai(validator(max_length = 65536))]
+    pub input: PatchValue<String>,
+    /// The expected output of the solution.
+    #[oai(validator(max_length = 65536))]
+    pub output: PatchValue<Option<String>>,
+    /// The data that is passed to the evaluator.
+    pub data: PatchValue<Option<Value>>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct Similarity {
+    /// The unique identifier of the pair of similar submissions.
+    pub id: Uuid,
+    /// The coding challenge both submissions belong to.
+    pub challenge_id: Uuid,
+    /// The first submission.
+    pub submission_a: Uuid,
+    /// The second submission.
+    pub submission_b: Uuid,
+    /// The creator of the first submission.
+    pub user_a: Uuid,
+    /// The creator of the second submission.
+    pub user_b: Uuid,
+    /// The share of fingerprints both submissions have in common (between `0`
+    /// and `1`).
+    pub similarity: f64,
+    /// The timestamp at which the similarity has been detected.
+    pub creation_timestamp: DateTime<Utc>,
+    /// Whether an admin has already resolved this pair.
+    pub resolved: bool,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct ResolveSimilarityRequest {
+    pub action: ResolveSimilarityAction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[oai(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ResolveSimilarityAction {
+    /// The submissions are not plagiarized.
+    Dismiss,
+    /// Ban the creator of the first submission from submitting solutions.
+    BlockFirst,
+    /// Ban the creator of the second submission from submitting solutions.
+    BlockSecond,
+    /// Ban both creators from submitting solutions.
+    BlockBoth,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct RunCodeRequest {
+    #[oai(flatten)]
+    pub content: SubmissionContent,
+    /// The input to pass to the program via stdin.
+    #[oai(validator(max_length = 65536))]
+    pub stdin: String,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct RunCodeResult {
+    /// The result of the compile step. Empty if the environment does not
+    /// require compilation.
+    pub compile: Option<RunResult>,
+    /// The result of the run step. Empty if the compilation failed.
+    pub run: Option<RunResult>,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct DebugEvaluatorRequest {
+    /// The draft of the evaluator.
+    #[oai(validator(max_length = 65536))]
+    pub evaluator: String,
+    /// The environment the evaluator is written for.
+    #[oai(default = "evaluator_environment_default")]
+    pub evaluator_environment: String,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct DebugGenerateRequest {
+    #[oai(flatten)]
+    pub evaluator: DebugEvaluatorRequest,
+    /// The seed of the test case to generate.
+    #[oai(validator(max_length = 256))]
+    pub seed: String,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct DebugPrepareRequest {
+    #[oai(flatten)]
+    pub evaluator: DebugEvaluatorRequest,
+    /// The seed of the test case.
+    #[oai(validator(max_length = 256))]
+    pub seed: String,
+    #[oai(flatten)]
+    pub solution: SubmissionContent,
+    /// The additional data generated for the test case.
+    #[oai(default)]
+    pub data: Value,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct DebugCheckRequest {
+    #[oai(flatten)]
+    pub evaluator: DebugEvaluatorRequest,
+    /// The seed of the test case.
+    #[oai(validator(max_length = 256))]
+    pub seed: String,
+    /// The output of the solution to check.
+    #[oai(validator(max_length = 65536))]
+    pub output: String,
+    /// The additional data generated for the test case.
+    #[oai(default)]
+    pub data: Value,
+}
+
+#[derive(Debug, Clone, Object)]
+pub struct DebugEvaluatorResult {
+    /// The result of the compile step. Empty if the environment does not
+    /// require compilation.
+    pub compile: Option<RunResult>,
+    /// The result of the run step, including the raw stdout and stderr of the
+    /// evaluator. Empty if the compilation failed.
+    pub run: Option<RunResult>,
+    /// The stdout of the evaluator parsed as JSON. Empty if the evaluator did
+    /// not produce valid JSON.
+    pub output: Option<Value>,
 }
 
 #[derive(Debug, Clone, Object)]
@@ -194,6 +639,18 @@ pub struct ExecutorConfig {
     pub time_limit: u64,
     /// The maximum `memory_limit` in megabytes.
     pub memory_limit: u64,
+    /// The multipliers applied to the limits of coding challenges for
+    /// solutions written for specific environments. The resulting limits never
+    /// exceed `time_limit` and `memory_limit`.
+    pub limit_multipliers: HashMap<String, LimitMultipliers>,
+}
+
+#[derive(Debug, Clone, Copy, Object)]
+pub struct LimitMultipliers {
+    /// The factor the time limit is multiplied with.
+    pub time: f64,
+    /// The factor the memory limit is multiplied with.
+    pub memory: f64,
 }
 
 impl CodingChallengeSummary {
@@ -204,19 +661,46 @@ impl CodingChallengeSummary {
             memory_limit: cc.memory_limit as _,
             static_tests: cc.static_tests as _,
             random_tests: cc.random_tests as _,
+            scoring_mode: cc.scoring_mode,
+            checker: cc.checker,
             subtask,
         }
     }
 }
 
 impl CodingChallenge {
-    pub fn from(cc: challenges_coding_challenges::Model, subtask: Subtask) -> Self {
+    pub fn from(
+        cc: challenges_coding_challenges::Model,
+        subtask: Subtask,
+        config: &CodingChallenges,
+        executor: &ExecutorConfig,
+    ) -> Self {
+        let environment_limits = config
+            .limit_multipliers
+            .iter()
+            .map(|(environment, multipliers)| {
+                let limits = Limits {
+                    time_limit: multipliers
+                        .time_limit(cc.time_limit as _)
+                        .min(executor.time_limit),
+                    memory_limit: multipliers
+                        .memory_limit(cc.memory_limit as _)
+                        .min(executor.memory_limit),
+                };
+                (environment.clone(), limits)
+            })
+            .collect();
         Self {
             description: cc.description,
             time_limit: cc.time_limit as _,
             memory_limit: cc.memory_limit as _,
             static_tests: cc.static_tests as _,
             random_tests: cc.random_tests as _,
+            scoring_mode: cc.scoring_mode,
+            checker: cc.checker,
+            evaluator_environment: cc.evaluator_environment,
+            environment_limits,
+            starter_code: serde_json::from_value(cc.starter_code).unwrap_or_default(),
             subtask,
         }
     }
@@ -246,7 +730,7 @@ impl From<CheckResult<RunResult>> for CheckResult<RunSummary> {
 impl Submission {
     pub fn from(
         submission: &challenges_coding_challenge_submissions::Model,
-        result: Option<CheckResult<RunSummary>>,
+        result: Option<challenges_coding_challenge_result::Model>,
         queue_position: Option<usize>,
     ) -> Self {
         Self {
@@ -255,8 +739,18 @@ impl Submission {
             creator: submission.creator,
             creation_timestamp: submission.creation_timestamp.and_utc(),
             environment: submission.environment.clone(),
-            result,
+            score: result.as_ref().map(|result| result.score),
+            failed_test: result.as_ref().and_then(|result| {
+                Some(FailedTest {
+                    index: result.failed_index? as _,
+                    seed: result.failed_seed.clone()?,
+                    input: result.failed_input.clone(),
+                    expected_output: result.failed_expected_output.clone(),
+                })
+            }),
+            result: result.map(Into::into),
             queue_position,
+            shared: submission.shared,
         }
     }
 }
@@ -292,11 +786,79 @@ impl From<challenges_coding_challenge_result::Model> for CheckResult<RunSummary>
     }
 }
 
+impl From<challenges_coding_challenge_test_result::Model> for TestResult {
+    fn from(value: challenges_coding_challenge_test_result::Model) -> Self {
+        Self {
+            index: value.index as _,
+            seed: value.seed,
+            verdict: value.verdict,
+            reason: value.reason,
+            status: value.run_status,
+            resource_usage: value.run_time.zip(value.run_memory).map(|(time, memory)| {
+                ResourceUsage {
+                    time: time as _,
+                    memory: memory as _,
+                }
+            }),
+        }
+    }
+}
+
+impl EvaluatorRevision {
+    pub fn from(revision: challenges_coding_challenge_evaluators::Model, current: Uuid) -> Self {
+        Self {
+            id: revision.id,
+            creator: revision.creator,
+            creation_timestamp: revision.creation_timestamp.and_utc(),
+            environment: revision.environment,
+            current: revision.id == current,
+        }
+    }
+}
+
+impl From<challenges_coding_challenge_similarities::Model> for Similarity {
+    fn from(value: challenges_coding_challenge_similarities::Model) -> Self {
+        Self {
+            id: value.id,
+            challenge_id: value.challenge_id,
+            submission_a: value.submission_a,
+            submission_b: value.submission_b,
+            user_a: value.user_a,
+            user_b: value.user_b,
+            similarity: value.similarity,
+            creation_timestamp: value.creation_timestamp.and_utc(),
+            resolved: value.resolved,
+        }
+    }
+}
+
+impl From<challenges_coding_challenge_test_data::Model> for TestData {
+    fn from(value: challenges_coding_challenge_test_data::Model) -> Self {
+        Self {
+            id: value.id,
+            creation_timestamp: value.creation_timestamp.and_utc(),
+            input: value.input,
+            output: value.output,
+            data: value.data,
+        }
+    }
+}
+
 impl From<PublicConfig> for ExecutorConfig {
     fn from(value: PublicConfig) -> Self {
         Self {
             time_limit: (value.run_limits.time - 1) * 1000,
             memory_limit: value.run_limits.memory,
+            limit_multipliers: HashMap::new(),
+        }
+    }
+}
+
+impl From<challenges::LimitMultipliers> for LimitMultipliers {
+    fn from(value: challenges::LimitMultipliers) -> Self {
+        Self {
+            time: value.time,
+            memory: value.memory,
         }
     }
 }
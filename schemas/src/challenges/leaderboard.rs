@@ -1,6 +1,6 @@
 use chrono::{DateTime, TimeZone, Utc};
 use lib::services;
-use poem_openapi::Object;
+use poem_openapi::{Enum, Object};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -35,6 +35,17 @@ pub struct Rank {
     pub rank: u64,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum, Serialize, Deserialize)]
+#[oai(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PerformanceMetric {
+    /// Rank users by the run time (in milliseconds) of their fastest accepted
+    /// submission.
+    RunTime,
+    /// Rank users by the memory usage (in KB) of their leanest accepted
+    /// submission.
+    RunMemory,
+}
+
 impl From<services::auth::User> for User {
     fn from(value: services::auth::User) -> Self {
         Self {